@@ -0,0 +1,1657 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, ToTokens};
+use syn::{parenthesized, parse::ParseStream, parse_quote, punctuated::Punctuated, spanned::Spanned, Attribute, Error, Field, Fields, GenericArgument, GenericParam, Generics, Ident, Lifetime, LifetimeParam, LitInt, LitStr, Path, PathArguments, PredicateLifetime, PredicateType, ReturnType, Token, Type, TypeParamBound, Variant, Visibility, WhereClause, WherePredicate};
+
+use crate::util::{find_generic_candidates, find_generic_candidates_bounds, find_generic_candidates_tokens, from_arms, parse_attr_variants, parse_poly_attr, parse_poly_vis, rename_arms, replace_self, snake_case, snake_case_name, try_cast_arms, uncastable_self, variant_pattern};
+
+/// The primitive types a `repr` can give the discriminant.
+pub const PRIMITIVES: &[&str] = &["u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize"];
+
+/// A `repr` shared by the parent and every sub-enum, making by-reference casts sound.
+pub struct Layout {
+	/// Every `repr` attribute of the parent, copied as is to the sub-enums
+	pub repr_attrs: Vec<Attribute>,
+	/// The primitive type of the discriminant, or `None` for `repr(C)` alone where the target decides its size
+	pub repr_ty: Option<Ident>,
+	/// Whether the fields follow a `repr(C)` union of every variant, whose alignment depends on the variants
+	pub c: bool,
+	/// Pad every sub-enum to the parent's size and alignment, set by `layout = shared`
+	pub shared: bool,
+}
+
+impl Layout {
+	/// Finds the `repr` attributes, if any, and the type of the discriminant they imply
+	pub fn from_attrs(attrs: &[Attribute]) -> Option<syn::Result<Self>> {
+		let repr_attrs = attrs.iter().filter(|a| a.path().is_ident("repr")).cloned().collect::<Vec<_>>();
+		let first = repr_attrs.first()?;
+
+		let mut repr_ty = None;
+		let mut c = false;
+		for attr in &repr_attrs {
+			let parsed = attr.parse_nested_meta(|meta| {
+				let Some(ident) = meta.path.get_ident() else {
+					return Err(meta.error("Unsupported repr"));
+				};
+				if PRIMITIVES.iter().any(|p| ident == p) {
+					if repr_ty.is_some() {
+						return Err(meta.error("Conflicting discriminant types"));
+					}
+					repr_ty = Some(ident.clone());
+				} else if ident == "C" {
+					c = true;
+				} else if ident == "align" {
+					let content;
+					parenthesized!(content in meta.input);
+					content.parse::<LitInt>()?;
+				} else if ident != "Rust" {
+					return Err(meta.error(format!("`repr({ident})` isn't supported on a hierarchy")));
+				}
+				Ok(())
+			});
+			if let Err(err) = parsed {
+				return Some(Err(err));
+			}
+		}
+
+		// Without `C` or a primitive type, the compiler is free to lay out every enum differently
+		if repr_ty.is_none() && !c {
+			return Some(Err(Error::new(
+				first.span(),
+				"The repr must be `C` or a primitive type for sub-enums to share the layout, eg: #[repr(u8)]",
+			)));
+		}
+		Some(Ok(Self {repr_attrs, repr_ty, c, shared: false}))
+	}
+
+	/// The type of the discriminant expressions, which is `isize` unless a primitive type is given
+	pub fn discriminant_ty(&self) -> Ident {
+		self.repr_ty.clone().unwrap_or_else(|| Ident::new("isize", Span::call_site()))
+	}
+}
+
+/// The derives of each sub-enum, from the parent's `#[derive(..)]` or `#[poly_derive(..)]` attributes.
+#[derive(Default)]
+pub struct Derives {
+	/// Applied to every sub-enum
+	pub common: Vec<Path>,
+	/// Left out of every sub-enum, eg `#[poly_derive(!Default)]`
+	pub excluded: Vec<Path>,
+	/// Added to and left out of a single sub-enum, eg `#[poly_derive(Int: Hash, Eq, !Default)]`, in the order the
+	/// sub-enums are first named
+	pub per_sub: Vec<(Ident, Vec<Path>, Vec<Path>)>,
+}
+
+impl Derives {
+	/// Parses the contents of a `#[derive(..)]`, applied to every sub-enum
+	pub fn parse_derive(&mut self, attr: &Attribute) -> syn::Result<()> {
+		self.common.extend(attr.parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)?);
+		Ok(())
+	}
+
+	/// Parses the contents of a `#[poly_derive(..)]`. Derives are common until a `Sub:` prefix, after which they
+	/// only apply to `Sub`, and a `!` leaves a derive out instead.
+	pub fn parse_poly_derive(&mut self, attr: &Attribute) -> syn::Result<()> {
+		attr.parse_args_with(|input: ParseStream| {
+			let mut sub = None;
+			while !input.is_empty() {
+				if input.peek(Ident) && input.peek2(Token![:]) && !input.peek2(Token![::]) {
+					let ident = input.parse::<Ident>()?;
+					input.parse::<Token![:]>()?;
+					sub = Some(ident);
+				}
+				let exclude = input.parse::<Option<Token![!]>>()?.is_some();
+				let path = input.call(Path::parse_mod_style)?;
+				let (included, excluded) = match &sub {
+					Some(sub) => {
+						if !self.per_sub.iter().any(|(k, ..)| k == sub) {
+							self.per_sub.push((sub.clone(), Vec::new(), Vec::new()));
+						}
+						let (_, included, excluded) = self.per_sub.iter_mut().find(|(k, ..)| k == sub).unwrap();
+						(included, excluded)
+					},
+					None => (&mut self.common, &mut self.excluded),
+				};
+				if exclude {excluded} else {included}.push(path);
+				if !input.is_empty() {
+					input.parse::<Token![,]>()?;
+				}
+			}
+			Ok(())
+		})
+	}
+
+	/// The derives of the sub-enum `k`, leaving out the excluded ones by their last segment so `!Default` also
+	/// matches `std::default::Default`
+	fn of(&self, k: &Ident) -> Vec<&Path> {
+		let per_sub = self.per_sub.iter().find(|(k2, ..)| k2 == k);
+		let (included, excluded) = per_sub.map_or((&[][..], &[][..]), |(_, i, e)| (&i[..], &e[..]));
+		let name = |path: &Path| path.segments.last().map(|s| s.ident.clone());
+		let mut derives = Vec::<&Path>::new();
+		for path in self.common.iter().chain(included) {
+			let excluded = self.excluded.iter().chain(excluded).any(|e| name(e) == name(path));
+			if !excluded && !derives.iter().any(|d| name(d) == name(path)) {
+				derives.push(path);
+			}
+		}
+		derives
+	}
+}
+
+/// Where the generated enums are declared and what they're called, set by `module = ..`, `prefix = ..` and
+/// `suffix = ..`.
+#[derive(Default)]
+pub struct Naming {
+	/// A module nested next to the parent, declaring every sub-enum and view
+	pub module: Option<Ident>,
+	pub prefix: Option<Ident>,
+	pub suffix: Option<Ident>,
+}
+
+/// The visibility in a module nested next to the parent that is equivalent to `vis` next to the parent.
+fn nested_vis(vis: &Visibility) -> Visibility {
+	match vis {
+		Visibility::Public(_) => vis.clone(),
+		Visibility::Inherited => parse_quote! {pub(super)},
+		Visibility::Restricted(restricted) => {
+			let path = &restricted.path;
+			match path.segments.first() {
+				Some(first) if first.ident == "crate" => vis.clone(),
+				Some(first) if first.ident == "self" => {
+					let rest = path.segments.iter().skip(1);
+					parse_quote! {pub(in super #(::#rest)*)}
+				},
+				_ => parse_quote! {pub(in super::#path)},
+			}
+		},
+	}
+}
+
+/// Attributes given to a variant and its fields in a single enum of the hierarchy with `#[poly_attr(..)]`.
+#[derive(Default)]
+pub struct VariantAttrs {
+	/// By the name of the enum they're given in, `Self` for the parent, in the order they're first given
+	pub variant: Vec<(Ident, Vec<Attribute>)>,
+	/// The same for each field
+	pub fields: Vec<Vec<(Ident, Vec<Attribute>)>>,
+}
+
+/// Removes the `#[poly_attr(..)]` attributes, returning what they give each enum, and skipping any that don't parse.
+fn take_poly_attrs(attrs: &mut Vec<Attribute>, errors: &mut Vec<Error>) -> Vec<(Ident, Vec<Attribute>)> {
+	let mut given = Vec::<(Ident, Vec<Attribute>)>::new();
+	for attr in attrs.iter().filter(|attr| attr.path().is_ident("poly_attr")) {
+		match parse_poly_attr(attr) {
+			Ok((k, attrs)) => match given.iter_mut().find(|(k2, _)| *k2 == k) {
+				Some((_, given)) => given.extend(attrs),
+				None => given.push((k, attrs)),
+			},
+			Err(err) => errors.push(err),
+		}
+	}
+	attrs.retain(|attr| !attr.path().is_ident("poly_attr"));
+	given
+}
+
+/// How `partition` routes a variant that is a member of several sub-enums.
+pub enum Partition {
+	/// Into the first of its sub-enums in this order, then in the order they are first mentioned
+	Priority(Vec<Ident>),
+	/// Into every one of its sub-enums, cloning it
+	Clone,
+}
+
+/// How the `as_*` accessors borrow a sub-enum.
+enum Access {
+	/// Through `CastRef`
+	Ref,
+	/// Through `CastView`
+	View,
+	/// Not at all, as no by-reference cast exists
+	None,
+}
+
+/// Everything needed to generate the sub-enums of a hierarchy and the impls between them.
+pub struct Hierarchy {
+	pub ident: Ident,
+	pub vis: Visibility,
+	pub generics: Generics,
+	/// The parent's variants with any `#[poly_enum(..)]` and `#[poly_attr(..)]` attributes removed
+	pub variants: Vec<Variant>,
+	/// The `#[poly_attr(..)]` attributes of each variant
+	pub variant_attrs: Vec<VariantAttrs>,
+	/// The members and generics of each sub-enum, only iterated through `order` to keep the output deterministic
+	pub sub_types: HashMap<Ident, (BTreeSet<usize>, Generics)>,
+	/// The sub-enums in the order they are first mentioned
+	pub order: Vec<Ident>,
+	pub partition: Partition,
+	pub derives: Derives,
+	/// The parent's attributes copied to every sub-enum, see [`inherited_attrs`]
+	pub attrs: Vec<Attribute>,
+	/// Attributes given to a single sub-enum with `#[poly_attr(Sub, ..)]`
+	pub sub_attrs: HashMap<Ident, Vec<Attribute>>,
+	/// Visibilities given to a single sub-enum with `#[poly_vis(Sub, ..)]`, instead of the parent's
+	pub sub_vis: HashMap<Ident, Visibility>,
+	pub layout: Option<Layout>,
+	pub naming: Naming,
+	/// Mistakes in the declaration, emitted alongside everything that could still be generated
+	pub errors: Vec<Error>,
+	/// Declarations that work but are likely mistakes, emitted as warnings with [`Hierarchy::note`]
+	pub warnings: Vec<(Span, String)>,
+}
+
+/// The name of a generic parameter, without the `'` of a lifetime.
+fn param_ident(p: &GenericParam) -> Ident {
+	match p {
+		GenericParam::Const(c) => c.ident.clone(),
+		GenericParam::Lifetime(lt) => lt.lifetime.ident.clone(),
+		GenericParam::Type(ty) => ty.ident.clone(),
+	}
+}
+
+/// The generic parameters `filter` accepts in the associated types `bounds` bind, eg `V` in `I: Iterator<Item = V>`.
+/// These are constrained by the bounded type, so they are needed alongside it.
+fn bound_candidates(bounds: &Punctuated<TypeParamBound, Token![+]>, filter: impl Fn(&Ident)->bool + Clone) -> Vec<Ident> {
+	bounds.iter().flat_map(|bound| match bound {
+		TypeParamBound::Trait(bound) => bound.path.segments.iter().flat_map(|segment| match &segment.arguments {
+			PathArguments::AngleBracketed(angled) => angled.args.iter().flat_map(|arg| match arg {
+				GenericArgument::AssocType(assoc) => find_generic_candidates(&assoc.ty, filter.clone()),
+				_ => Vec::new(),
+			}).collect(),
+			PathArguments::Parenthesized(parens) => match &parens.output {
+				ReturnType::Default => Vec::new(),
+				ReturnType::Type(_, ty) => find_generic_candidates(ty, filter.clone()),
+			},
+			PathArguments::None => Vec::new(),
+		}).collect(),
+		_ => Vec::new(),
+	}).collect()
+}
+
+/// Keeps the parameters of `generics` named in `kept`, in their order, and the bounds, defaults and where clause
+/// predicates that only mention those.
+fn kept_generics(generics: &Generics, kept: &HashSet<Ident>) -> Generics {
+	let is_param = |ident: &Ident| generics.params.iter().any(|p| param_ident(p) == *ident);
+	let all_kept = |idents: Vec<Ident>| idents.iter().all(|ident| kept.contains(ident));
+	let kept_bounds = |bounds: &Punctuated<TypeParamBound, Token![+]>| bounds.iter().filter(|bound| {
+		all_kept(find_generic_candidates_bounds([*bound], is_param))
+	}).cloned().collect::<Punctuated<_, _>>();
+	let kept_lifetimes = |bounds: &Punctuated<Lifetime, Token![+]>| bounds.iter().filter(|bound| {
+		kept.contains(&bound.ident)
+	}).cloned().collect::<Punctuated<_, _>>();
+
+	// The parameters keep the parent's order, so the sub-enum's type is the same on every build
+	let mut required = Generics::default();
+	for p in generics.params.iter().filter(|p| kept.contains(&param_ident(p))) {
+		let mut p = p.clone();
+		match &mut p {
+			GenericParam::Const(c) => if c.default.as_ref().is_some_and(|default| {
+				!all_kept(find_generic_candidates_tokens(default.to_token_stream(), is_param))
+			}) {
+				(c.eq_token, c.default) = (None, None);
+			},
+			GenericParam::Lifetime(lt) => lt.bounds = kept_lifetimes(&lt.bounds),
+			GenericParam::Type(ty) => {
+				ty.bounds = kept_bounds(&ty.bounds);
+				if ty.default.as_ref().is_some_and(|default| !all_kept(find_generic_candidates(default, is_param))) {
+					(ty.eq_token, ty.default) = (None, None);
+				}
+			},
+		}
+		required.params.push(p);
+	}
+
+	if let Some(where_clause) = &generics.where_clause {
+		let predicates = where_clause.predicates.iter().filter_map(|predicate| match predicate {
+			WherePredicate::Lifetime(pl) if kept.contains(&pl.lifetime.ident) => {
+				let bounds = kept_lifetimes(&pl.bounds);
+				(!bounds.is_empty()).then(|| WherePredicate::Lifetime(PredicateLifetime {bounds, ..pl.clone()}))
+			},
+			WherePredicate::Type(pt) if all_kept(find_generic_candidates(&pt.bounded_ty, is_param)) => {
+				let bounds = kept_bounds(&pt.bounds);
+				(!bounds.is_empty()).then(|| WherePredicate::Type(PredicateType {bounds, ..pt.clone()}))
+			},
+			WherePredicate::Lifetime(_) | WherePredicate::Type(_) => None,
+			_ => all_kept(find_generic_candidates_tokens(predicate.to_token_stream(), is_param)).then(|| predicate.clone()),
+		}).collect::<Punctuated<_, _>>();
+		if !predicates.is_empty() {
+			required.where_clause = Some(WhereClause {predicates, ..where_clause.clone()});
+		}
+	}
+	required
+}
+
+/// Keeps the parameters of `generics` that are used by `types`, and the bounds and where clause predicates about them.
+fn required_generics<'a>(generics: &Generics, types: impl Iterator<Item = &'a Type>) -> Generics {
+	let generics_set = generics.params.iter().map(|p| (param_ident(p), p)).collect::<HashMap<_, _>>();
+	let is_param = |ident: &Ident| generics_set.contains_key(ident);
+
+	let mut required_generics = types.flat_map(|ty| {
+		find_generic_candidates(ty, is_param)
+	}).collect::<HashSet<_>>();
+	// Parameters only used in the associated types bound on those that are kept are needed too, until no more are added
+	loop {
+		let bounds = generics.params.iter().filter_map(|p| match p {
+			GenericParam::Type(ty) if required_generics.contains(&ty.ident) => Some(&ty.bounds),
+			_ => None,
+		});
+		let predicates = generics.where_clause.iter().flat_map(|clause| &clause.predicates).filter_map(|predicate| match predicate {
+			WherePredicate::Type(pt) if find_generic_candidates(&pt.bounded_ty, is_param).iter().any(|ident| {
+				required_generics.contains(ident)
+			}) => Some(&pt.bounds),
+			_ => None,
+		});
+		let implied = bounds.chain(predicates).flat_map(|bounds| bound_candidates(bounds, is_param)).filter(|ident| {
+			!required_generics.contains(ident)
+		}).collect::<Vec<_>>();
+		if implied.is_empty() {
+			break;
+		}
+		required_generics.extend(implied);
+	}
+	kept_generics(generics, &required_generics)
+}
+
+/// The parent's attributes that sub-enums inherit. Docs, derives, `repr` and this crate's attributes are handled
+/// separately, and lint levels, `non_exhaustive` and `must_use` are always inherited. Attributes of other macros are
+/// only inherited if `with_helpers`, as the derives they belong to may not be.
+pub fn inherited_attrs(attrs: &[Attribute], with_helpers: bool) -> Vec<Attribute> {
+	const HANDLED: &[&str] = &["doc", "derive", "repr", "poly_attr", "poly_derive", "poly_enum", "poly_vis"];
+	const BUILTIN: &[&str] = &["allow", "warn", "deny", "forbid", "expect", "non_exhaustive", "must_use"];
+	attrs.iter().filter(|attr| {
+		let is = |names: &[&str]| names.iter().any(|name| attr.path().is_ident(name));
+		!is(HANDLED) && (with_helpers || is(BUILTIN))
+	}).cloned().collect()
+}
+
+/// Whether the field holds values of the enum it's declared in.
+fn is_self(f: &Field) -> bool {
+	!find_generic_candidates(&f.ty, |ident| ident == "Self").is_empty()
+}
+
+/// Checks the values nested in `variants` before any is moved, so a failed cast can give them back untouched.
+fn check_nested<'a>(dst_ty: &TokenStream, mut variants: impl Iterator<Item = &'a Variant>) -> Option<TokenStream> {
+	variants.any(|v| v.fields.iter().any(is_self)).then(|| quote! {
+		if let Err(err) = ::poly_enum::CastCheck::<#dst_ty>::check_cast(&self) {
+			return Err(err.map(|()| self));
+		}
+	})
+}
+
+fn field_types(v: &Variant) -> impl Iterator<Item = &Type> {
+	v.fields.iter().map(|f| &f.ty)
+}
+
+impl Hierarchy {
+	pub fn new<'a>(
+		ident: Ident,
+		vis: Visibility,
+		generics: Generics,
+		variants: impl IntoIterator<Item = &'a Variant>,
+	) -> Self {
+		let mut errors = Vec::new();
+		let mut warnings = Vec::new();
+		let mut sub_type_map: HashMap<_, BTreeSet<_>> = HashMap::new();
+		let mut order = Vec::new();
+		let mut stripped_variants = Vec::new();
+		let mut variant_attrs = Vec::new();
+		for variant in variants {
+			let lists = variant.attrs.iter().filter(|attr| if let Some(ident) = attr.meta.path().get_ident() {
+				ident == "poly_enum"
+			} else {false}).filter_map(|attr| {
+				attr.meta.require_list().map_err(|err| errors.push(err)).ok().map(|list| parse_attr_variants(list, &mut errors))
+			}).collect::<Vec<_>>();
+			for k in lists.into_iter().flatten() {
+				if k == ident {
+					errors.push(Error::new(k.span(), format!("`{k}` is the name of the parent enum")));
+					continue;
+				}
+				if !sub_type_map.contains_key(&k) {
+					order.push(k.clone());
+				}
+				// Each attribute is checked on its own, so a sub-enum can still be repeated across them
+				if !sub_type_map.entry(k.clone()).or_default().insert(stripped_variants.len()) {
+					errors.push(Error::new(k.span(), format!("`{k}` is already listed")));
+				}
+			}
+
+			let mut stripped_variant = variant.clone();
+			stripped_variant.attrs.retain(|attr| if let Some(ident) = attr.meta.path().get_ident() {
+				ident != "poly_enum"
+			} else {true});
+			variant_attrs.push(VariantAttrs {
+				variant: take_poly_attrs(&mut stripped_variant.attrs, &mut errors),
+				fields: stripped_variant.fields.iter_mut().map(|f| take_poly_attrs(&mut f.attrs, &mut errors)).collect(),
+			});
+			stripped_variants.push(stripped_variant);
+		}
+
+		// Only the fields wrapping `Self` in a type implementing `Cast` can be cast along with the enum, so the variants
+		// holding it otherwise are left out of every sub-enum, and those left empty aren't generated
+		for (u, v) in stripped_variants.iter().enumerate() {
+			let uncastable = v.fields.iter().filter_map(|f| uncastable_self(&f.ty)).map(|ty| {
+				Error::new_spanned(ty, "`Self` can only be held directly, or in a `Box`, `Rc`, `Arc` or `Vec`")
+			}).collect::<Vec<_>>();
+			if !uncastable.is_empty() {
+				errors.extend(uncastable);
+				sub_type_map.values_mut().for_each(|members| {members.remove(&u);});
+			}
+		}
+		sub_type_map.retain(|_, members| !members.is_empty());
+		order.retain(|k| sub_type_map.contains_key(k));
+
+		for (i, k) in order.iter().enumerate() {
+			let members = &sub_type_map[k];
+			if members.len() == stripped_variants.len() {
+				errors.push(Error::new(k.span(), format!("`{k}` contains every variant, so it would be a copy of `{ident}`")));
+			} else if let Some(k2) = order[..i].iter().find(|k2| sub_type_map[*k2] == *members) {
+				// Both are still generated, converting into each other with `From`
+				warnings.push((k.span(), format!("`{k}` contains the same variants as `{k2}`, so it is a copy of it")));
+			}
+		}
+
+		for ((u, v), attrs) in stripped_variants.iter().enumerate().zip(&variant_attrs) {
+			let given = attrs.variant.iter().chain(attrs.fields.iter().flatten()).map(|(k, _)| k);
+			errors.extend(given.filter(|k| *k != "Self").filter(|k| !sub_type_map.get(*k).is_some_and(|idx| idx.contains(&u))).map(|k| {
+				Error::new(k.span(), format!("`{}` is not in a sub-enum `{k}`", v.ident))
+			}));
+		}
+
+		let sub_types = sub_type_map.into_iter().map(|(k, variant_idx)| {
+			let sub_generics = required_generics(
+				&generics,
+				variant_idx.iter().copied().filter_map(|u| stripped_variants.get(u)).flat_map(field_types),
+			);
+			(k, (variant_idx, sub_generics))
+		}).collect();
+
+		Self {
+			ident,
+			vis,
+			generics,
+			variants: stripped_variants,
+			variant_attrs,
+			sub_types,
+			order,
+			partition: Partition::Priority(Vec::new()),
+			derives: Derives::default(),
+			attrs: Vec::new(),
+			sub_attrs: HashMap::new(),
+			sub_vis: HashMap::new(),
+			layout: None,
+			naming: Naming::default(),
+			errors,
+			warnings,
+		}
+	}
+
+	/// Sets the layout, checking that its discriminant type can tell every variant apart, including the padding, which
+	/// is left out if it doesn't fit
+	pub fn set_layout(&mut self, mut layout: Layout) {
+		if let Some(repr_ty) = &layout.repr_ty
+			&& let Ok(bits) = repr_ty.to_string()[1..].parse::<u32>()
+			&& bits < 128
+		{
+			let needed = self.variants.len() as u128 + layout.shared as u128;
+			if needed > 1 << bits {
+				let padding = if layout.shared {", including the padding of `layout = shared`"} else {""};
+				self.errors.push(Error::new(
+					repr_ty.span(),
+					format!("`{repr_ty}` can only tell {} variants apart, but there are {needed}{padding}", 1u128 << bits),
+				));
+				layout.shared = false;
+			}
+		}
+		self.layout = Some(layout);
+	}
+
+	/// Sets the derives of the sub-enums, checking that every sub-enum they name exists
+	pub fn set_derives(&mut self, derives: Derives) {
+		for (k, ..) in derives.per_sub.iter().filter(|(k, ..)| !self.sub_types.contains_key(k)) {
+			self.errors.push(self.unknown_sub(k));
+		}
+		self.derives = derives;
+	}
+
+	/// The error for a sub-enum named in an attribute that no variant is in
+	pub fn unknown_sub(&self, k: &Ident) -> Error {
+		Error::new(k.span(), format!("`{k}` is not a sub-enum of `{}`", self.ident))
+	}
+
+	/// Reads the parent's `#[poly_attr(Sub, ..)]` and `#[poly_vis(Sub, ..)]` attributes
+	pub fn parse_sub_attrs(&mut self, attrs: &[Attribute]) {
+		for attr in attrs {
+			let parsed = if attr.path().is_ident("poly_attr") {
+				parse_poly_attr(attr).map(|(k, attrs)| {
+					// Casts by reference rely on every enum of the hierarchy sharing the parent's layout
+					let (repr, attrs) = attrs.into_iter().partition::<Vec<_>, _>(|attr| attr.path().is_ident("repr"));
+					self.errors.extend(repr.iter().map(|attr| Error::new_spanned(
+						&attr.meta,
+						format!("`{k}` shares the layout of `{}`, so it can't be given its own `repr`", self.ident),
+					)));
+					self.sub_attrs.entry(k.clone()).or_default().extend(attrs);
+					k
+				})
+			} else if attr.path().is_ident("poly_vis") {
+				parse_poly_vis(attr).map(|(k, vis)| {
+					self.sub_vis.insert(k.clone(), vis);
+					k
+				})
+			} else {
+				continue;
+			};
+			match parsed {
+				Ok(k) if !self.sub_types.contains_key(&k) => self.errors.push(self.unknown_sub(&k)),
+				Ok(_) => {},
+				Err(err) => self.errors.push(err),
+			}
+		}
+	}
+
+	/// The parent's variant `u` as declared in the sub-enum `k`, with the attributes given to `k` and without those only
+	/// given to the parent
+	fn sub_variant(&self, u: usize, k: &Ident) -> Option<Variant> {
+		let mut variant = self.variants.get(u)?.clone();
+		let given = self.variant_attrs.get(u)?;
+		let apply = |attrs: &mut Vec<Attribute>, given: &[(Ident, Vec<Attribute>)]| {
+			// The derive can't change the parent, so its attributes are written as usual as well as given to `Self`
+			let parent_only = given.iter().filter(|(k, _)| *k == "Self").flat_map(|(_, attrs)| attrs).map(|attr| {
+				attr.to_token_stream().to_string()
+			}).collect::<HashSet<_>>();
+			attrs.retain(|attr| !parent_only.contains(&attr.to_token_stream().to_string()));
+			attrs.extend(given.iter().filter(|(k2, _)| k2 == k).flat_map(|(_, attrs)| attrs).cloned());
+		};
+		apply(&mut variant.attrs, &given.variant);
+		for (field, given) in variant.fields.iter_mut().zip(&given.fields) {
+			apply(&mut field.attrs, given);
+		}
+		Some(variant)
+	}
+
+	/// The sub-enums in the order they are first mentioned, with their members and generics
+	fn subs(&self) -> impl Iterator<Item = (&Ident, &(BTreeSet<usize>, Generics))> {
+		self.order.iter().filter_map(|k| Some((k, self.sub_types.get(k)?)))
+	}
+
+	/// The visibility of the sub-enum `k`
+	fn vis_of(&self, k: &Ident) -> &Visibility {
+		self.sub_vis.get(k).unwrap_or(&self.vis)
+	}
+
+	/// The name the sub-enum `k` is declared with, after any prefix and suffix
+	fn sub_ident(&self, k: &Ident) -> Ident {
+		let prefix = self.naming.prefix.as_ref().map(Ident::to_string).unwrap_or_default();
+		let suffix = self.naming.suffix.as_ref().map(Ident::to_string).unwrap_or_default();
+		format_ident!("{prefix}{k}{suffix}", span = k.span())
+	}
+
+	/// The path to a generated enum from next to the parent
+	fn generated_path(&self, ident: &Ident) -> TokenStream {
+		match &self.naming.module {
+			Some(module) => quote! {#module::#ident},
+			None => quote! {#ident},
+		}
+	}
+
+	/// The path to the sub-enum `k` from next to the parent
+	fn sub_path(&self, k: &Ident) -> TokenStream {
+		self.generated_path(&self.sub_ident(k))
+	}
+
+	/// The visibility the sub-enum `k` and its views are declared with, in their module if any
+	fn declared_vis(&self, k: &Ident) -> Visibility {
+		match &self.naming.module {
+			Some(_) => nested_vis(self.vis_of(k)),
+			None => self.vis_of(k).clone(),
+		}
+	}
+
+	/// Declares the generated enums, in their module if any
+	fn declare(&self, declarations: Vec<TokenStream>) -> TokenStream {
+		match &self.naming.module {
+			Some(module) => {
+				let vis = &self.vis;
+				let doc = format!("The sub-enums of [`{}`]", self.ident);
+				quote! {
+					#[doc = #doc]
+					#vis mod #module {
+						#[allow(unused_imports)]
+						use super::*;
+
+						#(#declarations)*
+					}
+				}
+			},
+			None => quote! {#(#declarations)*},
+		}
+	}
+
+	/// The member of a sub-enum that keeps references from being reinterpreted as it, if any.
+	///
+	/// Without `layout = shared` the sub-enum may be smaller than the enum it's cast from, so a nested `Self` would be
+	/// cloned or dropped with the wrong layout. With `repr(C)` its fields may also sit at other offsets.
+	fn cast_ref_blocker(&self, variant_idx: &BTreeSet<usize>) -> Option<&Variant> {
+		let layout = self.layout.as_ref()?;
+		if layout.shared {
+			return None;
+		}
+		let mut members = variant_idx.iter().copied().filter_map(|u| self.variants.get(u));
+		members.clone().find(|v| v.fields.iter().any(is_self)).or_else(|| {
+			members.find(|v| layout.c && !v.fields.is_empty())
+		})
+	}
+
+	/// Whether the sub-enum `k` is borrowed through views rather than by reinterpreting references.
+	fn has_views(&self, k: &Ident) -> bool {
+		self.layout.is_none() || self.sub_types.get(k).is_some_and(|(variant_idx, _)| self.cast_ref_blocker(variant_idx).is_some())
+	}
+
+	/// A warning at `span`, as notes can't be emitted on stable. Raised as a deprecation, so the parent's lint
+	/// levels can silence it. `expect` is left out, as it would go unfulfilled here.
+	fn note(&self, span: Span, msg: &str) -> TokenStream {
+		const LINTS: &[&str] = &["allow", "warn", "deny", "forbid"];
+		let lints = self.attrs.iter().filter(|attr| LINTS.iter().any(|name| attr.path().is_ident(name)));
+		let note = Ident::new("poly_enum", span);
+		quote! {
+			#(#lints)*
+			const _: () = {
+				#[deprecated(note = #msg)]
+				#[allow(non_upper_case_globals)]
+				const poly_enum: () = ();
+				#note
+			};
+		}
+	}
+
+	/// Leaves out the sub-enums named like the parent, its partition or another's views, or whose partition field would
+	/// be `remainder`, with an error for each.
+	fn check_names(&mut self) {
+		let partition = format_ident!("{}Partition", self.ident);
+		let views = self.order.iter().filter(|k| self.has_views(k)).flat_map(|k| {
+			let sub_ident = self.sub_ident(k);
+			[format_ident!("{sub_ident}Ref"), format_ident!("{sub_ident}Mut")].map(|view| (view, k))
+		}).collect::<Vec<_>>();
+		let clashes = self.order.iter().filter_map(|k| {
+			let sub_ident = self.sub_ident(k);
+			let msg = if self.naming.module.is_none() && (sub_ident == self.ident || sub_ident == partition) {
+				format!("`{sub_ident}` is already the name of a generated item of `{}`", self.ident)
+			} else if let Some((_, k2)) = views.iter().find(|(view, _)| *view == sub_ident) {
+				format!("`{sub_ident}` is already the name of a borrowed view of `{k2}`")
+			} else if snake_case(k) == "remainder" {
+				format!("`{partition}` already has a `remainder` field for the values left over, so `{k}` can't be a sub-enum")
+			} else {
+				return None;
+			};
+			Some((k.clone(), Error::new(k.span(), msg)))
+		}).collect::<Vec<_>>();
+		for (k, err) in clashes {
+			self.order.retain(|k2| *k2 != k);
+			self.sub_types.remove(&k);
+			self.errors.push(err);
+		}
+	}
+
+	pub fn expand(&mut self) -> TokenStream {
+		self.check_names();
+		let errors = self.errors.iter().map(Error::to_compile_error);
+		let warnings = self.warnings.iter().map(|(span, msg)| self.note(*span, msg));
+
+		let enum_ident = &self.ident;
+		let stripped_variants = &self.variants;
+		let (parent_impl_generics, parent_ty_generics, parent_where_clause) = self.generics.split_for_impl();
+
+		let (padding, padding_error) = match self.padding_variant() {
+			Some(Ok(padding)) => (Some(padding), None),
+			Some(Err(err)) => (None, Some(err.into_compile_error())),
+			None => (None, None),
+		};
+
+		let (declarations, sub_types) = self.subs().map(|(name, (variant_idx, generics))| {
+			let k = &self.sub_path(name);
+			let variants = variant_idx.iter().copied().filter_map(|u| self.sub_variant(u, name));
+			let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+			let members = variant_idx.iter().copied().collect::<Vec<_>>();
+			let member_index = members.iter().copied().filter_map(|u| Some((u, stripped_variants.get(u)?))).map(|(u, v)| {
+				let pattern = variant_pattern(k, v);
+				quote! {#pattern => #u,}
+			});
+			let variant_name = members.iter().copied().filter_map(|u| stripped_variants.get(u)).map(|v| {
+				let pattern = variant_pattern(k, v);
+				let name = LitStr::new(&v.ident.to_string(), v.ident.span());
+				quote! {#pattern => #name,}
+			});
+			let padding_arm = padding.is_some().then(|| quote! {#k::__PolyEnumPadding(_, never) => match never {},});
+
+			let from_variant = from_arms(k, enum_ident, variant_idx.iter().copied().filter_map(|u| stripped_variants.get(u)));
+
+			let cast_variant = try_cast_arms(enum_ident, k, variant_idx.iter().copied().filter_map(|u| stripped_variants.get(u)));
+			let nested_check = check_nested(&quote! {#k #ty_generics}, variant_idx.iter().copied().filter_map(|u| stripped_variants.get(u)));
+			let target_name = LitStr::new(&self.sub_ident(name).to_string(), name.span());
+
+			let cross_cast = self.subs().filter(|(name2, _v2)| {
+				name != *name2
+			}).map(|(name2, (variant_idx2, generics2))| {
+				let k2 = &self.sub_path(name2);
+				let cast_variant = try_cast_arms(k, k2, variant_idx.intersection(variant_idx2).copied().filter_map(|u| stripped_variants.get(u)));
+				let target_name = LitStr::new(&self.sub_ident(name2).to_string(), name2.span());
+
+				// Both sub-enums' parameters, in the parent's order so lifetimes stay first
+				let generics_set = generics.params.iter().chain(&generics2.params).map(param_ident).collect::<HashSet<_>>();
+				let merged_generics = kept_generics(&self.generics, &generics_set);
+				let (_impl_generics2, ty_generics2, _where_clause2) = generics2.split_for_impl();
+				let nested_check = check_nested(
+					&quote! {#k2 #ty_generics2},
+					variant_idx.intersection(variant_idx2).copied().filter_map(|u| stripped_variants.get(u)),
+				);
+				let (merged_impl_generics, _merged_ty_generics, merged_where_clause) = merged_generics.split_for_impl();
+
+				// Every variant of a subset is a member of the superset, so widening can't fail
+				let widen = variant_idx.is_subset(variant_idx2).then(|| {
+					let from_variant = from_arms(k, k2, variant_idx.iter().copied().filter_map(|u| stripped_variants.get(u)));
+					quote! {
+						impl #merged_impl_generics From<#k #ty_generics> for #k2 #ty_generics2 #merged_where_clause {
+							fn from(value: #k #ty_generics) -> #k2 #ty_generics2 {
+								match value {
+									#(#from_variant)*
+								}
+							}
+						}
+					}
+				});
+
+				let cast_check = self.cast_check_impl(
+					&quote! {#merged_impl_generics},
+					&quote! {#merged_where_clause},
+					k,
+					&quote! {#k #ty_generics},
+					&quote! {#k2 #ty_generics2},
+					&target_name,
+					variant_idx.intersection(variant_idx2).copied().filter_map(|u| stripped_variants.get(u)),
+				);
+
+				// Every sub-enum has the parent's size, so pointers can be reinterpreted in place
+				let shared_layout = padding.is_some().then(|| {
+					let subset = variant_idx.is_subset(variant_idx2).then(|| quote! {
+						unsafe impl #merged_impl_generics ::poly_enum::layout::Subset<#k2 #ty_generics2> for #k #ty_generics #merged_where_clause {}
+					});
+					quote! {
+						unsafe impl #merged_impl_generics ::poly_enum::layout::SharedLayout<#k2 #ty_generics2> for #k #ty_generics #merged_where_clause {}
+						#subset
+					}
+				});
+
+				// Every value of the target must also be valid in the source, as `cast_mut` allows writing any of them
+				let cast_ref = (!self.has_views(name2) && variant_idx2.is_subset(variant_idx)).then(|| self.cast_ref_impl(
+					&quote! {#merged_impl_generics},
+					&quote! {#merged_where_clause},
+					k,
+					&quote! {#k #ty_generics},
+					k2,
+					&quote! {#k2 #ty_generics2},
+					variant_idx.intersection(variant_idx2).copied().filter_map(|u| stripped_variants.get(u)),
+				));
+
+				quote! {
+					impl #merged_impl_generics ::poly_enum::Cast<#k2 #ty_generics2> for #k #ty_generics #merged_where_clause {
+						#[inline]
+						fn cast(self) -> Option<#k2 #ty_generics2> {
+							::poly_enum::TryCast::try_cast(self).ok()
+						}
+					}
+
+					#widen
+
+					impl #merged_impl_generics ::poly_enum::TryCast<#k2 #ty_generics2> for #k #ty_generics #merged_where_clause {
+						fn try_cast(self) -> Result<#k2 #ty_generics2, ::poly_enum::CastError<Self>> {
+							#nested_check
+							#[allow(unreachable_patterns)]
+							match self {
+								#(#cast_variant)*
+								_ => {
+									let variant = ::poly_enum::Hierarchy::variant_name(&self);
+									Err(::poly_enum::CastError::new(self, variant, #target_name))
+								},
+							}
+						}
+					}
+
+					#cast_check
+
+					#cast_ref
+
+					#shared_layout
+				}
+			});
+
+			let derives = self.derives.of(name);
+			let vis = self.vis_of(name);
+			let attrs = &self.attrs;
+			let sub_attrs = self.sub_attrs.get(name).map(Vec::as_slice).unwrap_or_default();
+			let doc = (!sub_attrs.iter().any(|attr| attr.path().is_ident("doc"))).then(|| {
+				let doc = format!("A sub-enum of [`{enum_ident}`]");
+				quote! {#[doc = #doc]}
+			});
+			let repr_attrs = self.layout.iter().flat_map(|layout| &layout.repr_attrs);
+
+			let parent_ty = quote! {#enum_ident #parent_ty_generics};
+			let sub_ty = quote! {#k #ty_generics};
+			let cast_check = self.cast_check_impl(
+				&quote! {#parent_impl_generics},
+				&quote! {#parent_where_clause},
+				enum_ident,
+				&parent_ty,
+				&sub_ty,
+				&target_name,
+				variant_idx.iter().copied().filter_map(|u| stripped_variants.get(u)),
+			);
+			let widen_check = self.cast_check_impl(
+				&quote! {#parent_impl_generics},
+				&quote! {#parent_where_clause},
+				k,
+				&sub_ty,
+				&parent_ty,
+				&LitStr::new(&enum_ident.to_string(), enum_ident.span()),
+				variant_idx.iter().copied().filter_map(|u| stripped_variants.get(u)),
+			);
+
+			// Reinterpreting references is only sound when the layouts are shared, otherwise borrowed views are offered
+			let (views, by_ref) = if self.has_views(name) {
+				let (views, cast_view) = self.expand_cast_view(name, variant_idx, generics);
+				(Some(views), cast_view)
+			} else {
+				(None, self.expand_cast_ref(name, variant_idx, generics, padding.is_some()))
+			};
+
+			// Methods can only name sub-enums whose generics are all in scope
+			let params = generics.params.iter().map(param_ident).collect::<HashSet<_>>();
+			let accessors = self.order.iter().filter(|k2| *k2 != name).filter_map(|k2| {
+				let (variant_idx2, generics2) = self.sub_types.get(k2)?;
+				if variant_idx.is_disjoint(variant_idx2) || !generics2.params.iter().all(|p| params.contains(&param_ident(p))) {
+					return None;
+				}
+				let (_, ty_generics2, _) = generics2.split_for_impl();
+				let access = if !self.has_views(k2) && variant_idx2.is_subset(variant_idx) {
+					Access::Ref
+				} else {
+					Access::None
+				};
+				let path2 = self.sub_path(k2);
+				Some(self.accessors(k2, &quote! {#path2 #ty_generics2}, access))
+			}).collect::<Vec<_>>();
+			let variant_consts = self.variant_consts(vis, &members, padding_arm.as_ref());
+			let inherent = (!accessors.is_empty() || variant_consts.is_some()).then(|| quote! {
+				impl #impl_generics #k #ty_generics #where_clause {
+					#variant_consts
+
+					#(#accessors)*
+				}
+			});
+
+			let declared_vis = self.declared_vis(name);
+			let sub_ident = self.sub_ident(name);
+			let declaration = quote! {
+				#doc
+				#[derive(#(#derives),*)]
+				#(#repr_attrs)*
+				#(#attrs)*
+				#(#sub_attrs)*
+				#declared_vis enum #sub_ident #generics #where_clause {#(#variants,)* #padding}
+
+				#views
+			};
+
+			let impls = quote! {
+				impl #parent_impl_generics ::poly_enum::Cast<#k #ty_generics> for #enum_ident #parent_ty_generics #parent_where_clause {
+					#[inline]
+					fn cast(self) -> Option<#k #ty_generics> {
+						::poly_enum::TryCast::try_cast(self).ok()
+					}
+				}
+
+				impl #parent_impl_generics ::poly_enum::TryCast<#k #ty_generics> for #enum_ident #parent_ty_generics #parent_where_clause {
+					fn try_cast(self) -> Result<#k #ty_generics, ::poly_enum::CastError<Self>> {
+						#nested_check
+						#[allow(unreachable_patterns)]
+						match self {
+							#(#cast_variant)*
+							_ => {
+								let variant = ::poly_enum::Hierarchy::variant_name(&self);
+								Err(::poly_enum::CastError::new(self, variant, #target_name))
+							},
+						}
+					}
+				}
+
+				#cast_check
+
+				#widen_check
+
+				#by_ref
+
+				impl #parent_impl_generics From<#k #ty_generics> for #enum_ident #parent_ty_generics #parent_where_clause {
+					fn from(value: #k #ty_generics) -> #enum_ident #parent_ty_generics {
+						match value {
+							#(#from_variant)*
+						}
+					}
+				}
+
+				impl #parent_impl_generics ::poly_enum::Cast<#enum_ident #parent_ty_generics> for #k #ty_generics #parent_where_clause {
+					#[inline]
+					fn cast(self) -> Option<#enum_ident #parent_ty_generics> {
+						Some(self.into())
+					}
+				}
+
+				impl #parent_impl_generics ::poly_enum::TryCast<#enum_ident #parent_ty_generics> for #k #ty_generics #parent_where_clause {
+					#[inline]
+					fn try_cast(self) -> Result<#enum_ident #parent_ty_generics, ::poly_enum::CastError<Self>> {
+						Ok(self.into())
+					}
+				}
+
+				impl #impl_generics ::poly_enum::Hierarchy for #k #ty_generics #where_clause {
+					const MEMBERS: &'static [usize] = &[#(#members),*];
+					fn member_index(&self) -> usize {
+						match *self {
+							#(#member_index)*
+							#padding_arm
+						}
+					}
+					fn variant_name(&self) -> &'static str {
+						match *self {
+							#(#variant_name)*
+							#padding_arm
+						}
+					}
+				}
+
+				#inherent
+
+				#(#cross_cast)*
+			};
+			(declaration, impls)
+		}).unzip::<_, _, Vec<_>, Vec<_>>();
+		let declarations = self.declare(declarations);
+
+		let parent_accessors = self.order.iter().filter_map(|k| {
+			let (_, generics) = self.sub_types.get(k)?;
+			let (_, ty_generics, _) = generics.split_for_impl();
+			let access = if self.has_views(k) {Access::View} else {Access::Ref};
+			let path = self.sub_path(k);
+			Some(self.accessors(k, &quote! {#path #ty_generics}, access))
+		});
+		let parent_variant_consts = self.variant_consts(&self.vis, &(0..stripped_variants.len()).collect::<Vec<_>>(), None);
+
+		let parent_members = 0..stripped_variants.len();
+		let parent_member_index = stripped_variants.iter().enumerate().map(|(u, v)| {
+			let pattern = variant_pattern(enum_ident, v);
+			quote! {#pattern => #u,}
+		});
+		let parent_variant_name = stripped_variants.iter().map(|v| {
+			let pattern = variant_pattern(enum_ident, v);
+			let name = LitStr::new(&v.ident.to_string(), v.ident.span());
+			quote! {#pattern => #name,}
+		});
+
+		let partition = self.expand_partition();
+
+		quote! {
+			#(#errors)*
+
+			#(#warnings)*
+
+			#padding_error
+
+			#partition
+
+			impl #parent_impl_generics #enum_ident #parent_ty_generics #parent_where_clause {
+				#parent_variant_consts
+
+				#(#parent_accessors)*
+			}
+
+			impl #parent_impl_generics ::poly_enum::Hierarchy for #enum_ident #parent_ty_generics #parent_where_clause {
+				const MEMBERS: &'static [usize] = &[#(#parent_members),*];
+				fn member_index(&self) -> usize {
+					match *self {
+						#(#parent_member_index)*
+					}
+				}
+				fn variant_name(&self) -> &'static str {
+					match *self {
+						#(#parent_variant_name)*
+					}
+				}
+			}
+
+			#declarations
+
+			#(#sub_types)*
+		}
+	}
+
+	/// `ALL`, `COUNT`, `variant_index` and `from_variant_index` for an enum of the parent's `members`, if all are fieldless.
+	fn variant_consts(
+		&self,
+		vis: &Visibility,
+		members: &[usize],
+		padding_arm: Option<&TokenStream>,
+	) -> Option<TokenStream> {
+		let variants = members.iter().filter_map(|&u| self.variants.get(u)).collect::<Vec<_>>();
+		if variants.iter().any(|v| !v.fields.is_empty()) {
+			return None;
+		}
+
+		let count = variants.len();
+		let idents = variants.iter().map(|v| &v.ident).collect::<Vec<_>>();
+		let indices = 0..count;
+		let from_indices = 0..count;
+		Some(quote! {
+			/// Every variant, in the order they are declared in the parent
+			#vis const ALL: [Self; #count] = [#(Self::#idents),*];
+			/// The number of variants
+			#vis const COUNT: usize = #count;
+
+			/// The index of the variant within [`Self::ALL`]
+			#vis const fn variant_index(&self) -> usize {
+				match *self {
+					#(Self::#idents => #indices,)*
+					#padding_arm
+				}
+			}
+
+			/// The variant at `index` within [`Self::ALL`]
+			#vis const fn from_variant_index(index: usize) -> Option<Self> {
+				match index {
+					#(#from_indices => Some(Self::#idents),)*
+					_ => None,
+				}
+			}
+		})
+	}
+
+	/// `is_*`, `as_*`, `as_*_mut` and `into_*` methods casting to the sub-enum `target`.
+	fn accessors(&self, target: &Ident, target_ty: &TokenStream, access: Access) -> TokenStream {
+		// A method can't be more visible than the sub-enum it returns
+		let vis = self.vis_of(target);
+		let name = snake_case_name(target);
+		let is_ident = format_ident!("is_{}", name, span = target.span());
+		let as_ident = format_ident!("as_{}", name, span = target.span());
+		let as_mut_ident = format_ident!("as_{}_mut", name, span = target.span());
+		let into_ident = format_ident!("into_{}", name, span = target.span());
+		let target_path = self.sub_path(target).to_string().replace(' ', "");
+		let is_doc = format!("Returns true if the value, and any value nested in it, is a member of [`{target_path}`]");
+		let (as_doc, as_mut_doc) = match access {
+			// Views borrow nested values as they are, so only the outer variant is checked
+			Access::View => (
+				format!("Borrows the value as a view of [`{target_path}`] if its variant is a member, whatever values are nested in it"),
+				format!("Mutably borrows the value as a view of [`{target_path}`] if its variant is a member, whatever values are nested in it"),
+			),
+			_ => (
+				format!("Borrows the value as a [`{target_path}`] if it is a member"),
+				format!("Mutably borrows the value as a [`{target_path}`] if it is a member"),
+			),
+		};
+		let into_doc = format!("Casts the value to a [`{target_path}`], giving it back if it is not a member");
+
+		let by_ref = match access {
+			Access::Ref => Some((
+				quote! {Option<&#target_ty>},
+				quote! {::poly_enum::CastRef::<#target_ty>::cast_ref(self)},
+				quote! {Option<&mut #target_ty>},
+				quote! {::poly_enum::CastRef::<#target_ty>::cast_mut(self)},
+			)),
+			Access::View => Some((
+				quote! {Option<<Self as ::poly_enum::CastView<#target_ty>>::Ref<'_>>},
+				quote! {::poly_enum::CastView::<#target_ty>::cast_view(self)},
+				quote! {Option<<Self as ::poly_enum::CastView<#target_ty>>::Mut<'_>>},
+				quote! {::poly_enum::CastView::<#target_ty>::cast_view_mut(self)},
+			)),
+			Access::None => None,
+		};
+		let by_ref = by_ref.map(|(ref_ty, ref_body, mut_ty, mut_body)| quote! {
+			#[doc = #as_doc]
+			#[inline]
+			#vis fn #as_ident(&self) -> #ref_ty {
+				#ref_body
+			}
+
+			#[doc = #as_mut_doc]
+			#[inline]
+			#vis fn #as_mut_ident(&mut self) -> #mut_ty {
+				#mut_body
+			}
+		});
+
+		quote! {
+			#[doc = #is_doc]
+			#[inline]
+			#vis fn #is_ident(&self) -> bool {
+				::poly_enum::CastCheck::<#target_ty>::can_cast(self)
+			}
+
+			#by_ref
+
+			#[doc = #into_doc]
+			#[inline]
+			#vis fn #into_ident(self) -> Result<#target_ty, Self> {
+				::poly_enum::TryCast::<#target_ty>::try_cast(self).map_err(::poly_enum::CastError::into_inner)
+			}
+		}
+	}
+
+	/// A `{Parent}Partition` struct with a bucket per sub-enum, and `partition` on the parent to fill it.
+	fn expand_partition(&self) -> TokenStream {
+		let enum_ident = &self.ident;
+		let vis = &self.vis;
+		let (parent_impl_generics, parent_ty_generics, parent_where_clause) = self.generics.split_for_impl();
+		let partition_ident = format_ident!("{enum_ident}Partition");
+
+		let priority = match &self.partition {
+			Partition::Priority(priority) => Some(priority),
+			Partition::Clone => None,
+		};
+		let order = priority.into_iter().flatten().chain(&self.order).filter_map(|k| {
+			Some((k, self.sub_types.get(k)?))
+		}).fold(Vec::<(&Ident, &(BTreeSet<usize>, Generics))>::new(), |mut order, (k, sub)| {
+			if !order.iter().any(|(k2, _)| *k2 == k) {
+				order.push((k, sub));
+			}
+			order
+		});
+
+		let fields = order.iter().map(|(k, _)| snake_case(k)).collect::<Vec<_>>();
+		let field_tys = order.iter().map(|(k, (_, generics))| {
+			let (_, ty_generics, _) = generics.split_for_impl();
+			let path = self.sub_path(k);
+			quote! {#path #ty_generics}
+		}).collect::<Vec<_>>();
+		let field_docs = order.iter().map(|(k, _)| {
+			format!("Values that are members of [`{}`]", self.sub_path(k).to_string().replace(' ', ""))
+		});
+		let field_vis = order.iter().map(|(k, _)| self.sub_vis.get(*k).map_or_else(|| quote! {pub}, |vis| quote! {#vis}));
+
+		let arms = self.variants.iter().enumerate().map(|(u, v)| {
+			let pattern = variant_pattern(enum_ident, v);
+			let mut buckets = order.iter().zip(&fields).zip(&field_tys).filter(|(((_, (variant_idx, _)), _), _)| {
+				variant_idx.contains(&u)
+			}).map(|((_, field), ty)| (field, ty)).collect::<Vec<_>>();
+			if priority.is_some() {
+				buckets.truncate(1);
+			}
+			match buckets.split_last() {
+				Some(((field, ty), rest)) => {
+					let rest = rest.iter().map(|(field, ty)| quote! {
+						partition.#field.push(::poly_enum::Cast::<#ty>::cast(::std::clone::Clone::clone(&value)).unwrap());
+					});
+					quote! {
+						value @ #pattern => {
+							#(#rest)*
+							partition.#field.push(::poly_enum::Cast::<#ty>::cast(value).unwrap());
+						},
+					}
+				},
+				None => quote! {value @ #pattern => partition.remainder.push(value),},
+			}
+		});
+
+		let struct_doc = format!("The values of [`{enum_ident}`]s split by sub-enum, see [`{enum_ident}::partition`]");
+		let fn_doc = match priority {
+			Some(_) => "Splits the values into a bucket per sub-enum, putting each in the first of its sub-enums by priority",
+			None => "Splits the values into a bucket per sub-enum, cloning each into every one of its sub-enums",
+		};
+
+		quote! {
+			#[doc = #struct_doc]
+			#vis struct #partition_ident #parent_impl_generics #parent_where_clause {
+				#(
+					#[doc = #field_docs]
+					#field_vis #fields: Vec<#field_tys>,
+				)*
+				/// Values that are members of no sub-enum
+				pub remainder: Vec<#enum_ident #parent_ty_generics>,
+			}
+
+			impl #parent_impl_generics Default for #partition_ident #parent_ty_generics #parent_where_clause {
+				fn default() -> Self {
+					Self {
+						#(#fields: Vec::new(),)*
+						remainder: Vec::new(),
+					}
+				}
+			}
+
+			impl #parent_impl_generics #enum_ident #parent_ty_generics #parent_where_clause {
+				#[doc = #fn_doc]
+				#vis fn partition(values: impl IntoIterator<Item = Self>) -> #partition_ident #parent_ty_generics {
+					let mut partition = #partition_ident::default();
+					for value in values {
+						match value {
+							#(#arms)*
+						}
+					}
+					partition
+				}
+			}
+		}
+	}
+
+	/// The hidden variant giving a sub-enum the parent's size and alignment with `layout = shared`.
+	///
+	/// Array lengths can't depend on generic parameters, so this fails for enums generic over types or constants.
+	fn padding_variant(&self) -> Option<syn::Result<TokenStream>> {
+		if !self.layout.as_ref()?.shared {
+			return None;
+		}
+		if let Some(param) = self.generics.params.iter().find(|p| !matches!(p, GenericParam::Lifetime(_))) {
+			return Some(Err(Error::new(
+				param.span(),
+				"`layout = shared` can't pad sub-enums to the size of a generic enum, so they can't borrow it",
+			)));
+		}
+
+		// Never constructed, but the discriminant must still be distinct from every member's
+		let repr_ty = self.layout.as_ref()?.discriminant_ty();
+		let discriminants = self.variants.iter().filter_map(|v| v.discriminant.as_ref()).map(|(_, expr)| expr);
+		let discriminant = quote! {{
+			const TAKEN: &[#repr_ty] = &[#(#discriminants),*];
+			let mut candidate: #repr_ty = 0;
+			let mut i = 0;
+			while i < TAKEN.len() {
+				if TAKEN[i] == candidate {
+					candidate += 1;
+					i = 0;
+				} else {
+					i += 1;
+				}
+			}
+			candidate
+		}};
+
+		// Lifetimes don't affect the layout
+		let enum_ident = &self.ident;
+		let lifetimes = self.generics.lifetimes().map(|_| quote! {'static}).collect::<Vec<_>>();
+		let parent_ty = if lifetimes.is_empty() {
+			quote! {#enum_ident}
+		} else {
+			quote! {#enum_ident<#(#lifetimes),*>}
+		};
+		Some(Ok(quote! {
+			#[doc(hidden)]
+			__PolyEnumPadding(
+				::poly_enum::layout::Padding<
+					#parent_ty,
+					{::std::mem::size_of::<#parent_ty>() - ::std::mem::align_of::<#parent_ty>()},
+				>,
+				::std::convert::Infallible,
+			) = #discriminant
+		}))
+	}
+
+	/// Const assertions that each variant's fields sit at the same offsets in `src` and `dst`.
+	fn field_assertions<'a>(
+		&self,
+		src: &impl ToTokens,
+		src_ty: &TokenStream,
+		dst: &impl ToTokens,
+		dst_ty: &TokenStream,
+		variants: impl Iterator<Item = &'a Variant>,
+	) -> Vec<TokenStream> {
+		let Some(layout) = &self.layout else {
+			return Vec::new();
+		};
+		let discriminants = self.variants.iter().map(|v| match &v.discriminant {
+			Some((_, expr)) => quote! {#expr},
+			None => quote! {compile_error!("")},
+		}).collect::<Vec<_>>();
+
+		// A `repr(C)` tag is as large as a C enum with the same discriminants, which depends on the target
+		let write_tag = |v: &Variant| {
+			let discriminant = self.variants.iter().position(|v2| v2.ident == v.ident).map(|u| &discriminants[u]);
+			match &layout.repr_ty {
+				Some(repr_ty) => quote! {
+					unsafe {(uninit_src.as_mut_ptr() as *mut #repr_ty).write(#discriminant)};
+					unsafe {(uninit_dst.as_mut_ptr() as *mut #repr_ty).write(#discriminant)};
+				},
+				None => {
+					let tags = (0..self.variants.len()).map(|u| format_ident!("V{u}")).collect::<Vec<_>>();
+					let tag = self.variants.iter().position(|v2| v2.ident == v.ident).map(|u| &tags[u]);
+					quote! {
+						#[repr(C)]
+						#[allow(dead_code)]
+						enum Tag {#(#tags = #discriminants),*}
+						unsafe {(uninit_src.as_mut_ptr() as *mut Tag).write(Tag::#tag)};
+						unsafe {(uninit_dst.as_mut_ptr() as *mut Tag).write(Tag::#tag)};
+					}
+				},
+			}
+		};
+		let [src_name, dst_name] = [src.to_token_stream(), dst.to_token_stream()].map(|path| path.to_string().replace(' ', ""));
+		variants.map(|v| {
+			let ident = &v.ident;
+			let msg = LitStr::new(
+				&format!("`{ident}` has fields at different offsets in `{src_name}` and `{dst_name}`, try #[poly_enum(layout = shared)]"),
+				ident.span(),
+			);
+			match &v.fields {
+				Fields::Named(named) => {
+					let idents = named.named.iter().flat_map(|f| {f.ident.as_ref()}).collect::<Vec<_>>();
+					let idents_a = named.named.iter().flat_map(|f| {f.ident.as_ref()}).map(|ident| {
+						Ident::new(&format!("{ident}_a"), ident.span())
+					}).collect::<Vec<_>>();
+					let idents_b = named.named.iter().flat_map(|f| {f.ident.as_ref()}).map(|ident| {
+						Ident::new(&format!("{ident}_b"), ident.span())
+					}).collect::<Vec<_>>();
+
+					let write_tag = write_tag(v);
+					quote! {
+						const {
+							let mut uninit_src = ::std::mem::MaybeUninit::<#src_ty>::uninit();
+							let mut uninit_dst = ::std::mem::MaybeUninit::<#dst_ty>::uninit();
+							#write_tag
+							match unsafe {(uninit_src.assume_init_ref(), uninit_dst.assume_init_ref())} {
+								(#src::#ident{#(#idents: #idents_a),*}, #dst::#ident{#(#idents: #idents_b),*}) => {
+									#(
+										let a_offset = unsafe {uninit_src.as_ptr().byte_offset_from(#idents_a)};
+										let b_offset = unsafe {uninit_dst.as_ptr().byte_offset_from(#idents_b)};
+										assert!(a_offset == b_offset, #msg);
+									)*
+								},
+								_ => unreachable!(),
+							};
+						};
+					}
+				},
+				Fields::Unit => Default::default(),
+				Fields::Unnamed(unnamed) => {
+					let idents_a = unnamed.unnamed.iter().enumerate().map(|(idx, f)| {
+						Ident::new(&format!("e_{idx}_a"), f.span())
+					}).collect::<Vec<_>>();
+					let idents_b = unnamed.unnamed.iter().enumerate().map(|(idx, f)| {
+						Ident::new(&format!("e_{idx}_b"), f.span())
+					}).collect::<Vec<_>>();
+
+					let write_tag = write_tag(v);
+					quote! {
+						const {
+							let mut uninit_src = ::std::mem::MaybeUninit::<#src_ty>::uninit();
+							let mut uninit_dst = ::std::mem::MaybeUninit::<#dst_ty>::uninit();
+							#write_tag
+							match unsafe {(uninit_src.assume_init_ref(), uninit_dst.assume_init_ref())} {
+								(#src::#ident(#(#idents_a),*), #dst::#ident(#(#idents_b),*)) => {
+									#(
+										let a_offset = unsafe {uninit_src.as_ptr().byte_offset_from(#idents_a)};
+										let b_offset = unsafe {uninit_dst.as_ptr().byte_offset_from(#idents_b)};
+										assert!(a_offset == b_offset, #msg);
+									)*
+								},
+								_ => unreachable!(),
+							};
+						};
+					}
+				},
+			}
+		}).collect()
+	}
+
+	/// `CastCheck` from `src` to `dst`, passing for the shared `variants` when every value nested in them is a member too.
+	#[allow(clippy::too_many_arguments)]
+	fn cast_check_impl<'a>(
+		&self,
+		impl_generics: &TokenStream,
+		where_clause: &TokenStream,
+		src: &impl ToTokens,
+		src_ty: &TokenStream,
+		dst_ty: &TokenStream,
+		target_name: &LitStr,
+		variants: impl Iterator<Item = &'a Variant>,
+	) -> TokenStream {
+		// Fields containing `Self` hold the other enum, so every value nested in them must be checked as well
+		let check_variant = variants.map(|v| {
+			let ident = &v.ident;
+			let bindings = v.fields.iter().enumerate().map(|(idx, f)| if is_self(f) {
+				Ident::new(&format!("e_{idx}"), f.span())
+			} else {
+				Ident::new("_", f.span())
+			}).collect::<Vec<_>>();
+			let checks = v.fields.iter().zip(&bindings).filter(|(f, _)| is_self(f)).map(|(f, binding)| {
+				let target = replace_self(&f.ty, dst_ty);
+				quote! {::poly_enum::CastCheck::<#target>::check_cast(#binding)?;}
+			});
+			let pattern = match &v.fields {
+				Fields::Named(named) => {
+					let names = named.named.iter().flat_map(|f| f.ident.as_ref());
+					quote! {#src::#ident{#(#names: #bindings),*}}
+				},
+				Fields::Unit => quote! {#src::#ident},
+				Fields::Unnamed(_) => quote! {#src::#ident(#(#bindings),*)},
+			};
+			quote! {
+				#pattern => {
+					#(#checks)*
+					Ok(())
+				},
+			}
+		});
+
+		quote! {
+			impl #impl_generics ::poly_enum::CastCheck<#dst_ty> for #src_ty #where_clause {
+				fn check_cast(&self) -> Result<(), ::poly_enum::CastError<()>> {
+					#[allow(unreachable_patterns)]
+					match self {
+						#(#check_variant)*
+						_ => Err(::poly_enum::CastError::new((), ::poly_enum::Hierarchy::variant_name(self), #target_name)),
+					}
+				}
+			}
+		}
+	}
+
+	/// `CastRef` from `src` to `dst`, by reinterpreting references to the shared layout.
+	#[allow(clippy::too_many_arguments)]
+	fn cast_ref_impl<'a>(
+		&self,
+		impl_generics: &TokenStream,
+		where_clause: &TokenStream,
+		src: &impl ToTokens,
+		src_ty: &TokenStream,
+		dst: &impl ToTokens,
+		dst_ty: &TokenStream,
+		variants: impl Iterator<Item = &'a Variant> + Clone,
+	) -> TokenStream {
+		let field_assertions = self.field_assertions(src, src_ty, dst, dst_ty, variants.clone());
+
+		// The reference must stay in bounds and aligned, and nested values are cloned and dropped with the other enum's
+		// layout, see `cast_ref_blocker`
+		let size_assertion = if variants.clone().any(|v| v.fields.iter().any(is_self)) {
+			quote! {
+				const {
+					assert!(
+						::std::mem::size_of::<#src_ty>() == ::std::mem::size_of::<#dst_ty>()
+							&& ::std::mem::align_of::<#src_ty>() == ::std::mem::align_of::<#dst_ty>(),
+						"casting a variant containing `Self` by reference requires both enums to have the same size and alignment",
+					);
+				};
+			}
+		} else {
+			quote! {
+				const {
+					assert!(
+						::std::mem::size_of::<#dst_ty>() <= ::std::mem::size_of::<#src_ty>()
+							&& ::std::mem::align_of::<#dst_ty>() <= ::std::mem::align_of::<#src_ty>(),
+						"casting by reference requires the target to be no larger and no more aligned than the source",
+					);
+				};
+			}
+		};
+
+		quote! {
+			impl #impl_generics ::poly_enum::CastRef<#dst_ty> for #src_ty #where_clause {
+				fn cast_mut(&mut self) -> Option<&mut #dst_ty> {
+					#(#field_assertions)*
+					#size_assertion
+					::poly_enum::CastCheck::<#dst_ty>::can_cast(self).then(|| {
+						unsafe {::std::mem::transmute::<&mut #src_ty, &mut #dst_ty>(self)}
+					})
+				}
+				fn cast_ref(&self) -> Option<&#dst_ty> {
+					#(#field_assertions)*
+					#size_assertion
+					::poly_enum::CastCheck::<#dst_ty>::can_cast(self).then(|| {
+						unsafe {::std::mem::transmute::<&#src_ty, &#dst_ty>(self)}
+					})
+				}
+			}
+		}
+	}
+
+	/// `CastRef` from the parent, and `Borrow` and `AsRef` back to it when the sub-enum has the parent's size.
+	///
+	/// With padding, pointers can also be cast in place in both directions.
+	fn expand_cast_ref(&self, k: &Ident, variant_idx: &BTreeSet<usize>, generics: &Generics, padded: bool) -> TokenStream {
+		let enum_ident = &self.ident;
+		let (parent_impl_generics, parent_ty_generics, parent_where_clause) = self.generics.split_for_impl();
+		let (_impl_generics, ty_generics, _where_clause) = generics.split_for_impl();
+
+		let sub_path = self.sub_path(k);
+		let parent_ty = quote! {#enum_ident #parent_ty_generics};
+		let sub_ty = quote! {#sub_path #ty_generics};
+		let cast_ref = self.cast_ref_impl(
+			&quote! {#parent_impl_generics},
+			&quote! {#parent_where_clause},
+			enum_ident,
+			&parent_ty,
+			&sub_path,
+			&sub_ty,
+			variant_idx.iter().copied().filter_map(|u| self.variants.get(u)),
+		);
+
+		// Without padding a sub-enum is only known to be as large as the parent when no variant has fields
+		let borrow = (padded || self.variants.iter().all(|v| v.fields.is_empty())).then(|| {
+			let msg = LitStr::new(
+				&format!("`{}` must have the same size and alignment as `{enum_ident}` to borrow it", self.sub_ident(k)),
+				k.span(),
+			);
+			quote! {
+				impl #parent_impl_generics ::std::borrow::Borrow<#parent_ty> for #sub_ty #parent_where_clause {
+					fn borrow(&self) -> &#parent_ty {
+						const {
+							assert!(
+								::std::mem::size_of::<#sub_ty>() == ::std::mem::size_of::<#parent_ty>()
+									&& ::std::mem::align_of::<#sub_ty>() == ::std::mem::align_of::<#parent_ty>(),
+								#msg,
+							);
+						};
+						unsafe {::std::mem::transmute::<&#sub_ty, &#parent_ty>(self)}
+					}
+				}
+
+				impl #parent_impl_generics AsRef<#parent_ty> for #sub_ty #parent_where_clause {
+					#[inline]
+					fn as_ref(&self) -> &#enum_ident #parent_ty_generics {
+						::std::borrow::Borrow::borrow(self)
+					}
+				}
+			}
+		});
+
+		let shared_layout = padded.then(|| quote! {
+			unsafe impl #parent_impl_generics ::poly_enum::layout::SharedLayout<#sub_ty> for #parent_ty #parent_where_clause {}
+			unsafe impl #parent_impl_generics ::poly_enum::layout::SharedLayout<#parent_ty> for #sub_ty #parent_where_clause {}
+			unsafe impl #parent_impl_generics ::poly_enum::layout::Subset<#parent_ty> for #sub_ty #parent_where_clause {}
+		});
+
+		quote! {
+			#cast_ref
+
+			#borrow
+
+			#shared_layout
+		}
+	}
+
+	/// `CastView` from the parent, through generated `{Sub}Ref` and `{Sub}Mut` enums borrowing its fields.
+	///
+	/// Returns the declarations of the views separately from the impl, as they go next to the sub-enum.
+	fn expand_cast_view(&self, k: &Ident, variant_idx: &BTreeSet<usize>, generics: &Generics) -> (TokenStream, TokenStream) {
+		let enum_ident = &self.ident;
+		let vis = self.declared_vis(k);
+		let sub_ident = self.sub_ident(k);
+		let sub_path = self.sub_path(k);
+		let (parent_impl_generics, parent_ty_generics, parent_where_clause) = self.generics.split_for_impl();
+		let (_impl_generics, ty_generics, _where_clause) = generics.split_for_impl();
+
+		let variants = variant_idx.iter().copied().filter_map(|u| self.variants.get(u)).collect::<Vec<_>>();
+
+		// Fields borrow from the parent, so `Self` refers to the parent rather than the sub-enum
+		let parent_ty = quote! {#enum_ident #parent_ty_generics};
+		let field_tys = variants.iter().map(|v| {
+			v.fields.iter().map(|f| replace_self(&f.ty, &parent_ty)).collect::<Vec<_>>()
+		}).collect::<Vec<_>>();
+
+		let lifetime = Lifetime::new("'poly", k.span());
+		let mut view_generics = required_generics(&self.generics, field_tys.iter().flatten());
+		// Fieldless views borrow nothing, and an unused lifetime would be rejected
+		if field_tys.iter().any(|tys| !tys.is_empty()) {
+			view_generics.params.insert(0, GenericParam::Lifetime(LifetimeParam::new(lifetime.clone())));
+		}
+		let (_view_impl_generics, view_ty_generics, view_where_clause) = view_generics.split_for_impl();
+
+		let ref_ident = format_ident!("{sub_ident}Ref");
+		let mut_ident = format_ident!("{sub_ident}Mut");
+		let (ref_path, mut_path) = (self.generated_path(&ref_ident), self.generated_path(&mut_ident));
+		// Docs are kept so views pass `missing_docs` like the sub-enums
+		let docs = |attrs: &[Attribute]| attrs.iter().filter(|attr| attr.path().is_ident("doc")).cloned().collect::<Vec<_>>();
+		let view_variants = |mutability: TokenStream| variants.iter().zip(&field_tys).map(|(v, tys)| {
+			let ident = &v.ident;
+			let doc = docs(&v.attrs);
+			match &v.fields {
+				Fields::Named(named) => {
+					let names = named.named.iter().flat_map(|f| f.ident.as_ref());
+					let field_docs = named.named.iter().map(|f| docs(&f.attrs));
+					quote! {#(#doc)* #ident{#(#(#field_docs)* #names: &#lifetime #mutability #tys),*}}
+				},
+				Fields::Unit => quote! {#(#doc)* #ident},
+				Fields::Unnamed(_) => quote! {#(#doc)* #ident(#(&#lifetime #mutability #tys),*)},
+			}
+		}).collect::<Vec<_>>();
+		let ref_variants = view_variants(quote! {});
+		let mut_variants = view_variants(quote! {mut});
+
+		let view_ref = rename_arms(enum_ident, &ref_path, variants.iter().copied()).collect::<Vec<_>>();
+		let view_mut = rename_arms(enum_ident, &mut_path, variants.iter().copied()).collect::<Vec<_>>();
+		let ref_doc = format!("A borrowed view of a [`{enum_ident}`] that is a member of [`{sub_ident}`]");
+		let mut_doc = format!("A mutably borrowed view of a [`{enum_ident}`] that is a member of [`{sub_ident}`]");
+
+		let declarations = quote! {
+			#[doc = #ref_doc]
+			#vis enum #ref_ident #view_generics #view_where_clause {#(#ref_variants),*}
+
+			#[doc = #mut_doc]
+			#vis enum #mut_ident #view_generics #view_where_clause {#(#mut_variants),*}
+		};
+		let cast_view = quote! {
+			impl #parent_impl_generics ::poly_enum::CastView<#sub_path #ty_generics> for #enum_ident #parent_ty_generics #parent_where_clause {
+				type Ref<#lifetime> = #ref_path #view_ty_generics where Self: #lifetime;
+				type Mut<#lifetime> = #mut_path #view_ty_generics where Self: #lifetime;
+				fn cast_view(&self) -> Option<Self::Ref<'_>> {
+					#[allow(unreachable_patterns)]
+					Some(match self {
+						#(#view_ref)*
+						_ => return None,
+					})
+				}
+				fn cast_view_mut(&mut self) -> Option<Self::Mut<'_>> {
+					#[allow(unreachable_patterns)]
+					Some(match self {
+						#(#view_mut)*
+						_ => return None,
+					})
+				}
+			}
+		};
+		(declarations, cast_view)
+	}
+}
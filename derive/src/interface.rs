@@ -1,6 +1,18 @@
+use quote::{quote, quote_spanned};
+use syn::{parse, spanned::Spanned, Error, FnArg, Ident, Item, ItemFn, LitStr, Pat, Stmt, Type};
 
+/// Splits a parameter type into its referent and how it is borrowed.
+fn referent(ty: &Type) -> (Option<bool>, &Type) {
+	match ty {
+		Type::Reference(r) => (Some(r.mutability.is_some()), &r.elem),
+		_ => (None, ty),
+	}
+}
+
+fn type_string(ty: &Type) -> String {
+	quote! {#ty}.to_string()
+}
 
-#[proc_macro_attribute]
 pub fn interface(_attr: proc_macro::TokenStream, item: proc_macro::TokenStream) -> proc_macro::TokenStream {
 	let mut fn_def = match parse::<ItemFn>(item) {
 		Ok(e) => e,
@@ -9,6 +21,10 @@ pub fn interface(_attr: proc_macro::TokenStream, item: proc_macro::TokenStream)
 		},
 	};
 
+	if let Some(param) = fn_def.sig.generics.params.first() {
+		return Error::new(param.span(), "#[interface] functions cannot be generic").into_compile_error().into();
+	}
+
 	let overloads = fn_def.block.stmts.extract_if(0.., |stmt| {
 		matches!(stmt, Stmt::Item(Item::Fn(f)) if f.sig.ident == fn_def.sig.ident)
 	}).filter_map(|stmt| match stmt {
@@ -16,31 +32,136 @@ pub fn interface(_attr: proc_macro::TokenStream, item: proc_macro::TokenStream)
 		_ => unreachable!()
 	}).collect::<Vec<_>>();
 
-	// fn_def.block.stmts.push(Stmt::Expr(fn_def.span(), Expr::Match(ExprMatch {
-	// 	attrs: Default::default(),
-	// 	match_token: Default::default(),
-	// 	expr: Expr::Tuple(ExprTuple {
-	// 		attrs: Default::default(),
-	// 		paren_token: Default::default(),
-
-	// 	})
-	// })));
-
-	let params = fn_def.sig.inputs.iter().map(|input| match input {
-		FnArg::Receiver(recv) => Ident::new("self", recv.span()),
-		FnArg::Typed(typed) => match &*typed.pat {
-			Pat::Ident(ident) => ident.ident.clone(),
-			_ => todo!()
+	let mut params = Vec::with_capacity(fn_def.sig.inputs.len());
+	for input in &fn_def.sig.inputs {
+		match input {
+			FnArg::Receiver(recv) => {
+				return Error::new(recv.span(), "#[interface] functions cannot take self").into_compile_error().into();
+			},
+			FnArg::Typed(typed) => match &*typed.pat {
+				Pat::Ident(ident) => params.push((ident.ident.clone(), &*typed.ty)),
+				pat => return Error::new(pat.span(), "Expected identifier").into_compile_error().into(),
+			},
+		}
+	}
+
+	let mut overload_tys = Vec::with_capacity(overloads.len());
+	for overload in &overloads {
+		if overload.sig.inputs.len() != params.len() {
+			return Error::new(
+				overload.sig.inputs.span(),
+				format!("Expected {} parameters to match the interface", params.len()),
+			).into_compile_error().into();
+		}
+		let mut tys = Vec::with_capacity(params.len());
+		for (input, (_, param_ty)) in overload.sig.inputs.iter().zip(&params) {
+			match input {
+				FnArg::Receiver(recv) => {
+					return Error::new(recv.span(), "Overloads cannot take self").into_compile_error().into();
+				},
+				FnArg::Typed(typed) if referent(&typed.ty).0 != referent(param_ty).0 => {
+					return Error::new(
+						typed.ty.span(),
+						"Overload parameters must be borrowed the same way as the interface",
+					).into_compile_error().into();
+				},
+				FnArg::Typed(typed) => tys.push(&*typed.ty),
+			}
 		}
+		overload_tys.push(tys);
+	}
+
+	// Parameters that every overload takes unchanged are passed straight through
+	let poly = (0..params.len()).filter(|&j| {
+		overload_tys.iter().any(|tys| type_string(tys[j]) != type_string(params[j].1))
+	}).collect::<Vec<_>>();
+
+	let fn_ident = &fn_def.sig.ident;
+	let renamed = (0..overloads.len()).map(|i| {
+		Ident::new(&format!("__{fn_ident}_overload_{i}"), overloads[i].sig.ident.span())
+	}).collect::<Vec<_>>();
+	let overload_items = overloads.iter().zip(&renamed).map(|(overload, ident)| {
+		let mut overload = overload.clone();
+		overload.sig.ident = ident.clone();
+		overload
 	});
-	let cases = overloads.into_iter().map(|mut fn_def| {
-		//fn_def.sig.inputs
+
+	let overload_members = overload_tys.iter().map(|tys| {
+		let members = poly.iter().map(|&j| {
+			let (_, ty) = referent(tys[j]);
+			quote! {<#ty as ::poly_enum::Hierarchy>::MEMBERS}
+		});
+		quote! {&[#(#members),*]}
+	});
+	let param_members = poly.iter().map(|&j| {
+		let (_, ty) = referent(params[j].1);
+		quote! {<#ty as ::poly_enum::Hierarchy>::MEMBERS}
 	});
-	let match_expr = quote_spanned! {fn_def.span()=>
-		match (#(#params,)*) {}
+	let member_indices = poly.iter().map(|&j| {
+		let ident = &params[j].0;
+		match referent(params[j].1).0 {
+			Some(_) => quote! {::poly_enum::Hierarchy::member_index(&*#ident)},
+			None => quote! {::poly_enum::Hierarchy::member_index(&#ident)},
+		}
+	});
+
+	let signature = |tys: &[&Type]| {
+		format!("{fn_ident}({})", tys.iter().map(|ty| type_string(ty)).collect::<Vec<_>>().join(", "))
+	};
+	let ambiguous = (0..overloads.len()).flat_map(|i| (i + 1..overloads.len()).map(move |j| (i, j))).map(|(i, j)| {
+		let msg = LitStr::new(&format!(
+			"Overloads `{}` and `{}` are ambiguous",
+			signature(&overload_tys[i]),
+			signature(&overload_tys[j]),
+		), fn_ident.span());
+		quote! {Err(::poly_enum::dispatch::DispatchError::Ambiguous(#i, #j)) => panic!(#msg),}
+	}).collect::<Vec<_>>();
+	let uncovered = LitStr::new(&format!("Overloads of `{fn_ident}` do not cover every variant"), fn_ident.span());
+
+	let cases = overload_tys.iter().zip(&renamed).enumerate().map(|(i, (tys, ident))| {
+		let args = params.iter().zip(tys).map(|((param, param_ty), ty)| {
+			if type_string(ty) == type_string(param_ty) {
+				return quote! {#param};
+			}
+			match referent(ty) {
+				(None, ty) => quote! {::poly_enum::Cast::<#ty>::cast(#param).unwrap()},
+				(Some(false), ty) => quote! {::poly_enum::CastRef::<#ty>::cast_ref(#param).unwrap()},
+				(Some(true), ty) => quote! {::poly_enum::CastRef::<#ty>::cast_mut(#param).unwrap()},
+			}
+		});
+		quote! {Some(#i) => #ident(#(#args),*),}
+	});
+
+	let fallback = !fn_def.block.stmts.is_empty();
+	let fallback_case = if fallback {
+		let stmts = &fn_def.block.stmts;
+		quote! {_ => {#(#stmts)*}}
+	} else {
+		quote! {_ => unreachable!()}
+	};
+
+	let block = quote_spanned! {fn_def.block.span()=>
+		{
+			#(#overload_items)*
+
+			const __POLY_ENUM_OVERLOADS: &[&[&[usize]]] = &[#(#overload_members),*];
+			const _: () = match ::poly_enum::dispatch::check(__POLY_ENUM_OVERLOADS, &[#(#param_members),*], #fallback) {
+				Ok(()) => {},
+				#(#ambiguous)*
+				Err(::poly_enum::dispatch::DispatchError::Uncovered) => panic!(#uncovered),
+				Err(_) => unreachable!(),
+			};
+
+			match ::poly_enum::dispatch::select(__POLY_ENUM_OVERLOADS, &[#(#member_indices),*]) {
+				#(#cases)*
+				#fallback_case
+			}
+		}
+	};
+	fn_def.block = match syn::parse2(block) {
+		Ok(block) => Box::new(block),
+		Err(err) => return err.into_compile_error().into(),
 	};
 
-	let s = quote! {#fn_def}.to_string();
-	//Error::new(fn_def.span(), s).into_compile_error().into()
 	quote! {#fn_def}.into()
 }
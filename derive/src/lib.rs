@@ -1,13 +1,20 @@
 mod derive;
 mod attr_proc;
+mod hierarchy;
+mod interface;
 mod util;
 
+#[proc_macro_attribute]
+pub fn interface(attr: proc_macro::TokenStream, item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	interface::interface(attr, item)
+}
+
 #[proc_macro_attribute]
 pub fn poly_enum(attr: proc_macro::TokenStream, item: proc_macro::TokenStream) -> proc_macro::TokenStream {
 	attr_proc::poly_enum(attr, item)
 }
 
-#[proc_macro_derive(PolyEnum, attributes(poly_derive, poly_enum))]
+#[proc_macro_derive(PolyEnum, attributes(poly_attr, poly_derive, poly_enum, poly_vis))]
 pub fn poly_enum_derive(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
 	derive::poly_enum_derive(item)
 }
@@ -1,12 +1,17 @@
-use proc_macro2::TokenTree;
-use syn::{punctuated::Punctuated, GenericArgument, GenericParam, Ident, MetaList, PathArguments, PathSegment, ReturnType, Type};
+use proc_macro2::{Group, TokenStream, TokenTree};
+use quote::{format_ident, quote, ToTokens};
+use syn::{ext::IdentExt, parse::ParseStream, parse_quote, punctuated::Punctuated, spanned::Spanned, Attribute, Expr, Fields, GenericArgument, GenericParam, Ident, LitInt, Meta, MetaList, Path, PathArguments, PathSegment, ReturnType, Token, Type, TypeParamBound, Variant, Visibility};
 
+/// The identifiers in `ty` that `filter` accepts, used to find the generic parameters a type needs. Parts that can't be
+/// analysed, such as macros, keep every identifier they contain, so a parameter is only left out when it's unused.
 pub fn find_generic_candidates(
 	ty: &Type,
 	filter: impl Fn(&Ident)->bool + Clone,
 ) -> Vec<Ident> {
 	match ty {
-		Type::Array(arr) => find_generic_candidates(&arr.elem, filter),
+		Type::Array(arr) => find_generic_candidates(&arr.elem, filter.clone()).into_iter().chain(
+			find_generic_candidates_tokens(arr.len.to_token_stream(), filter)
+		).collect(),
 		Type::BareFn(bare) => {
 			bare.inputs.iter().flat_map(|input| {
 				find_generic_candidates(&input.ty, filter.clone())
@@ -22,6 +27,8 @@ pub fn find_generic_candidates(
 			}).collect()
 		},
 		Type::Group(group) => find_generic_candidates(&group.elem, filter),
+		Type::ImplTrait(impl_trait) => find_generic_candidates_bounds(&impl_trait.bounds, filter),
+		Type::Infer(_) | Type::Never(_) => Default::default(),
 		Type::Paren(paren) => find_generic_candidates(&paren.elem, filter),
 		Type::Path(path) => {
 			if let Some(qs) = &path.qself {
@@ -29,9 +36,7 @@ pub fn find_generic_candidates(
 					find_generic_candidates_path_segments(&path.path.segments, filter.clone())
 				).collect()
 			} else {
-				path.path.segments.first().iter().filter_map(|segment| {
-					filter(&segment.ident).then_some(&segment.ident).cloned()
-				}).chain(find_generic_candidates_path_segments(&path.path.segments, filter.clone())).collect()
+				find_generic_candidates_path(&path.path, filter)
 			}
 		}
 		Type::Ptr(ptr) => find_generic_candidates(&ptr.elem, filter),
@@ -41,10 +46,23 @@ pub fn find_generic_candidates(
 			})
 		).collect(),
 		Type::Slice(s) => find_generic_candidates(&s.elem, filter),
-		_ => Default::default(),
+		Type::TraitObject(object) => find_generic_candidates_bounds(&object.bounds, filter),
+		Type::Tuple(tuple) => tuple.elems.iter().flat_map(|ty| find_generic_candidates(ty, filter.clone())).collect(),
+		// Macros, verbatim tokens and any future kind of type
+		_ => find_generic_candidates_tokens(ty.to_token_stream(), filter),
 	}
 }
 
+/// The identifiers `filter` accepts in a path used as a type or trait, including its first segment.
+pub fn find_generic_candidates_path(
+	path: &Path,
+	filter: impl Fn(&Ident)->bool + Clone,
+) -> Vec<Ident> {
+	path.segments.first().iter().filter_map(|segment| {
+		filter(&segment.ident).then_some(&segment.ident).cloned()
+	}).chain(find_generic_candidates_path_segments(&path.segments, filter.clone())).collect()
+}
+
 pub fn find_generic_candidates_path_segments<P>(
 	segments: &Punctuated<PathSegment, P>,
 	filter: impl Fn(&Ident)->bool + Clone
@@ -54,7 +72,10 @@ pub fn find_generic_candidates_path_segments<P>(
 			angled.args.iter().flat_map(|a| match a {
 				GenericArgument::Lifetime(lt) => filter(&lt.ident).then_some(&lt.ident).cloned().into_iter().collect(),
 				GenericArgument::Type(ty) => find_generic_candidates(ty, filter.clone()),
-				_ => vec![],
+				GenericArgument::AssocType(assoc) => find_generic_candidates(&assoc.ty, filter.clone()),
+				GenericArgument::Constraint(constraint) => find_generic_candidates_bounds(&constraint.bounds, filter.clone()),
+				// Const expressions, and any future kind of argument
+				_ => find_generic_candidates_tokens(a.to_token_stream(), filter.clone()),
 			}).collect()
 		},
 		PathArguments::None => vec![],
@@ -67,17 +88,312 @@ pub fn find_generic_candidates_path_segments<P>(
 	}).collect()
 }
 
-pub fn parse_attr_variants(list: &MetaList) -> syn::Result<Vec<Ident>> {
-	let mut idents = Vec::new();
+/// The identifiers `filter` accepts in trait and lifetime bounds.
+pub fn find_generic_candidates_bounds<'a>(
+	bounds: impl IntoIterator<Item = &'a TypeParamBound>,
+	filter: impl Fn(&Ident)->bool + Clone,
+) -> Vec<Ident> {
+	bounds.into_iter().flat_map(|bound| match bound {
+		TypeParamBound::Trait(bound) => find_generic_candidates_path(&bound.path, filter.clone()),
+		TypeParamBound::Lifetime(lt) => filter(&lt.ident).then_some(&lt.ident).cloned().into_iter().collect(),
+		_ => find_generic_candidates_tokens(bound.to_token_stream(), filter.clone()),
+	}).collect()
+}
+
+/// Every identifier `filter` accepts in `tokens`, for the parts of a type that can't be analysed further.
+pub fn find_generic_candidates_tokens(tokens: TokenStream, filter: impl Fn(&Ident)->bool + Clone) -> Vec<Ident> {
+	tokens.into_iter().flat_map(|tt| match tt {
+		TokenTree::Ident(ident) => filter(&ident).then_some(ident).into_iter().collect(),
+		TokenTree::Group(group) => find_generic_candidates_tokens(group.stream(), filter.clone()),
+		_ => Vec::new(),
+	}).collect()
+}
+
+/// Parses the sub-enums listed in `#[poly_enum(..)]`, which must be distinct identifiers. Those before a mistake are
+/// still returned, along with the error.
+pub fn parse_attr_variants(list: &MetaList, errors: &mut Vec<syn::Error>) -> Vec<Ident> {
+	let mut idents = Vec::<Ident>::new();
 	let mut expecting_comma = false;
 	for e in list.tokens.clone().into_iter() {
-		match e {
-			TokenTree::Ident(ident) if !expecting_comma => idents.push(ident),
-			TokenTree::Punct(p) if expecting_comma && p.as_char() == ',' => {},
-			_ if expecting_comma => Err(syn::Error::new(e.span(), "Expected ','"))?,
-			_ => Err(syn::Error::new(e.span(), "Expected identifier"))?,
+		let err = match e {
+			TokenTree::Ident(ident) if !expecting_comma => {
+				if idents.contains(&ident) {
+					errors.push(syn::Error::new(ident.span(), format!("`{ident}` is already listed")));
+				} else {
+					idents.push(ident);
+				}
+				None
+			},
+			TokenTree::Punct(p) if expecting_comma && p.as_char() == ',' => None,
+			TokenTree::Punct(p) if expecting_comma && p.as_char() == ':' => {
+				idents.pop();
+				Some(syn::Error::new(p.span(), "Expected the name of a sub-enum, not a path"))
+			},
+			_ if expecting_comma => Some(syn::Error::new(e.span(), "Expected ','")),
+			_ => Some(syn::Error::new(e.span(), "Expected the name of a sub-enum")),
+		};
+		if let Some(err) = err {
+			errors.push(err);
+			return idents;
 		}
 		expecting_comma = !expecting_comma;
 	}
-	Ok(idents)
+	if idents.is_empty() {
+		errors.push(syn::Error::new_spanned(list, "Expected at least one sub-enum"));
+	}
+	idents
+}
+
+/// The part of `ty` holding `Self` in a way that can't be cast, as only `Box`, `Rc`, `Arc` and `Vec` implement `Cast`.
+pub fn uncastable_self(ty: &Type) -> Option<&Type> {
+	match ty {
+		Type::Group(group) => uncastable_self(&group.elem),
+		Type::Paren(paren) => uncastable_self(&paren.elem),
+		Type::Path(path) if path.qself.is_none() && path.path.is_ident("Self") => None,
+		Type::Path(path) if path.qself.is_none() => {
+			let segment = path.path.segments.last()?;
+			let wrapper = ["Box", "Rc", "Arc", "Vec"].iter().any(|w| segment.ident == w);
+			match &segment.arguments {
+				PathArguments::AngleBracketed(args) if wrapper && args.args.len() == 1 => match args.args.first() {
+					Some(GenericArgument::Type(inner)) => uncastable_self(inner),
+					_ => None,
+				},
+				_ => mentions_self(ty.to_token_stream()).then_some(ty),
+			}
+		},
+		_ => mentions_self(ty.to_token_stream()).then_some(ty),
+	}
+}
+
+/// Whether `Self` appears anywhere in the tokens.
+fn mentions_self(tokens: TokenStream) -> bool {
+	tokens.into_iter().any(|tt| match tt {
+		TokenTree::Ident(ident) => ident == "Self",
+		TokenTree::Group(group) => mentions_self(group.stream()),
+		_ => false,
+	})
+}
+
+/// Parses `#[poly_attr(Sub, attr, ..)]` into the enum and the attributes it's given.
+pub fn parse_poly_attr(attr: &Attribute) -> syn::Result<(Ident, Vec<Attribute>)> {
+	attr.parse_args_with(|input: ParseStream| {
+		// `Self` names the parent on variants and fields
+		let ident = input.call(Ident::parse_any)?;
+		input.parse::<Token![,]>()?;
+		let metas = Punctuated::<Meta, Token![,]>::parse_terminated(input)?;
+		Ok((ident, metas.into_iter().map(|meta| parse_quote! {#[#meta]}).collect()))
+	})
+}
+
+/// Parses `#[poly_vis(Sub, vis)]` into the sub-enum and its visibility, where an empty `vis` makes it private.
+pub fn parse_poly_vis(attr: &Attribute) -> syn::Result<(Ident, Visibility)> {
+	attr.parse_args_with(|input: ParseStream| {
+		let ident = input.parse::<Ident>()?;
+		input.parse::<Token![,]>()?;
+		Ok((ident, input.parse()?))
+	})
+}
+
+/// Converts a `CamelCase` identifier to `snake_case`, keeping acronyms together, eg `HTTPServer` to `http_server`.
+pub fn snake_case(ident: &Ident) -> Ident {
+	let snake = snake_case_name(ident);
+	// Keywords need to be raw, except those that can't be. syn doesn't know of `gen`, reserved since edition 2024
+	match snake.as_str() {
+		"crate" | "self" | "super" => format_ident!("{snake}_", span = ident.span()),
+		"gen" => Ident::new_raw(&snake, ident.span()),
+		_ if syn::parse_str::<Ident>(&snake).is_err() => Ident::new_raw(&snake, ident.span()),
+		_ => Ident::new(&snake, ident.span()),
+	}
+}
+
+/// The `snake_case` form of `ident` as a string, which may be a keyword, for use within a longer identifier.
+pub fn snake_case_name(ident: &Ident) -> String {
+	let name = ident.to_string();
+	let chars = name.chars().collect::<Vec<_>>();
+	let mut snake = String::with_capacity(name.len() + 4);
+	for (i, &c) in chars.iter().enumerate() {
+		if c.is_uppercase() && i > 0 {
+			let prev = chars[i - 1];
+			let next_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+			if prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_lower) {
+				snake.push('_');
+			}
+		}
+		snake.extend(c.to_lowercase());
+	}
+	snake
+}
+
+/// A pattern matching any value of the variant, ignoring its fields.
+pub fn variant_pattern(enum_ident: &impl ToTokens, v: &Variant) -> TokenStream {
+	let ident = &v.ident;
+	match &v.fields {
+		Fields::Named(_) => quote! {#enum_ident::#ident{..}},
+		Fields::Unit => quote! {#enum_ident::#ident},
+		Fields::Unnamed(_) => quote! {#enum_ident::#ident(..)},
+	}
+}
+
+/// Match arms moving each variant from `src` into `dst`, casting any fields that contain `Self`.
+/// If a nested cast fails, the original value is rebuilt and returned in the error.
+pub fn try_cast_arms<'a>(
+	src: &'a impl ToTokens,
+	dst: &'a impl ToTokens,
+	variants: impl Iterator<Item = &'a Variant> + 'a,
+) -> impl Iterator<Item = TokenStream> + 'a {
+	variants.map(move |v| {
+		let ident = &v.ident;
+		let (bindings, is_self) = match &v.fields {
+			Fields::Named(named) => named.named.iter().flat_map(|f| {
+				Some((f.ident.clone()?, !find_generic_candidates(&f.ty, |ident| ident == "Self").is_empty()))
+			}).unzip(),
+			Fields::Unit => (Vec::new(), Vec::new()),
+			Fields::Unnamed(unnamed) => unnamed.unnamed.iter().enumerate().map(|(idx, f)| {
+				(Ident::new(&format!("e_{idx}"), f.span()), !find_generic_candidates(&f.ty, |ident| ident == "Self").is_empty())
+			}).unzip::<_, _, Vec<_>, Vec<_>>(),
+		};
+		// `None` moves the binding across unchanged
+		let construct = |enum_ident: &dyn ToTokens, values: &[Option<TokenStream>]| {
+			let fields = bindings.iter().zip(values).map(|(b, value)| match (&v.fields, value) {
+				(Fields::Named(_), Some(value)) => quote! {#b: #value},
+				(_, Some(value)) => value.clone(),
+				(_, None) => quote! {#b},
+			});
+			match &v.fields {
+				Fields::Named(_) => quote! {#enum_ident::#ident{#(#fields),*}},
+				Fields::Unit => quote! {#enum_ident::#ident},
+				Fields::Unnamed(_) => quote! {#enum_ident::#ident(#(#fields),*)},
+			}
+		};
+
+		let plain = vec![None; bindings.len()];
+		let casts = is_self.iter().enumerate().filter(|(_, s)| **s).map(|(i, _)| {
+			let binding = &bindings[i];
+			let values = bindings.iter().enumerate().map(|(j, b)| (j < i && is_self[j]).then(|| {
+				quote! {::poly_enum::TryCast::try_cast(#b).unwrap_or_else(|_| unreachable!())}
+			})).collect::<Vec<_>>();
+			let original = construct(src, &values);
+			quote! {
+				let #binding = match ::poly_enum::TryCast::try_cast(#binding) {
+					Ok(e) => e,
+					Err(err) => return Err(err.map(|#binding| #original)),
+				};
+			}
+		});
+		let pattern = construct(src, &plain);
+		let result = construct(dst, &plain);
+		quote! {
+			#pattern => {
+				#(#casts)*
+				Ok(#result)
+			},
+		}
+	})
+}
+
+/// Match arms moving each variant from `src` into `dst` where every variant is known to be a member of `dst`.
+pub fn from_arms<'a>(
+	src: &'a impl ToTokens,
+	dst: &'a impl ToTokens,
+	variants: impl Iterator<Item = &'a Variant> + 'a,
+) -> impl Iterator<Item = TokenStream> + 'a {
+	variants.map(move |v| {
+		let ident = &v.ident;
+		match &v.fields {
+			Fields::Named(named) => {
+				let idents = named.named.iter().flat_map(|f| {f.ident.as_ref()}).collect::<Vec<_>>();
+				let conversions = named.named.iter().map(|f| if find_generic_candidates(&f.ty, |ident| ident == "Self").is_empty() {
+					let ident = f.ident.as_ref();
+					quote! {#ident}
+				} else {
+					let ident = f.ident.as_ref();
+					quote! {#ident: ::poly_enum::Cast::cast(#ident).unwrap()}
+				});
+				quote! {#src::#ident{#(#idents),*} => #dst::#ident{#(#conversions),*},}
+			},
+			Fields::Unit => quote! {#src::#ident => #dst::#ident,},
+			Fields::Unnamed(unnamed) => {
+				let idents = unnamed.unnamed.iter().enumerate().map(|(idx, f)| {
+					Ident::new(&format!("e_{idx}"), f.span())
+				}).collect::<Vec<_>>();
+				let conversions = unnamed.unnamed.iter().enumerate().map(|(idx, f)| {
+					let ident = Ident::new(&format!("e_{idx}"), f.span());
+					if find_generic_candidates(&f.ty, |ident| ident == "Self").is_empty() {
+						quote! {#ident}
+					} else {
+						quote! {::poly_enum::Cast::cast(#ident).unwrap()}
+					}
+				});
+				quote! {#src::#ident(#(#idents),*) => #dst::#ident(#(#conversions),*),}
+			},
+		}
+	})
+}
+
+/// Match arms moving each variant's fields unchanged from `src` into `dst`.
+pub fn rename_arms<'a>(
+	src: &'a impl ToTokens,
+	dst: &'a impl ToTokens,
+	variants: impl Iterator<Item = &'a Variant> + 'a,
+) -> impl Iterator<Item = TokenStream> + 'a {
+	variants.map(move |v| {
+		let ident = &v.ident;
+		match &v.fields {
+			Fields::Named(named) => {
+				let idents = named.named.iter().flat_map(|f| {f.ident.as_ref()}).collect::<Vec<_>>();
+				quote! {#src::#ident{#(#idents),*} => #dst::#ident{#(#idents),*},}
+			},
+			Fields::Unit => quote! {#src::#ident => #dst::#ident,},
+			Fields::Unnamed(unnamed) => {
+				let idents = unnamed.unnamed.iter().enumerate().map(|(idx, f)| {
+					Ident::new(&format!("e_{idx}"), f.span())
+				}).collect::<Vec<_>>();
+				quote! {#src::#ident(#(#idents),*) => #dst::#ident(#(#idents),*),}
+			},
+		}
+	})
+}
+
+/// Replaces every `Self` in `ty` with `with`.
+pub fn replace_self(ty: &Type, with: &TokenStream) -> Type {
+	fn replace(tokens: TokenStream, with: &TokenStream) -> TokenStream {
+		tokens.into_iter().flat_map(|tt| match tt {
+			TokenTree::Ident(ident) if ident == "Self" => with.clone(),
+			TokenTree::Group(group) => {
+				let mut replaced = Group::new(group.delimiter(), replace(group.stream(), with));
+				replaced.set_span(group.span());
+				TokenTree::Group(replaced).into()
+			},
+			tt => tt.into(),
+		}).collect()
+	}
+	syn::parse2(replace(ty.to_token_stream(), with)).unwrap_or_else(|_| ty.clone())
+}
+
+/// Gives every variant an explicit discriminant, following the implicit numbering rules.
+pub fn fill_discriminants(variants: &mut [Variant]) {
+	let mut base: Option<Expr> = None;
+	let mut offset = 0usize;
+	for v in variants {
+		match &v.discriminant {
+			Some((_, expr)) => {
+				base = Some(expr.clone());
+				offset = 0;
+			},
+			None => {
+				let expr = match &base {
+					Some(base) => {
+						let offset = LitInt::new(&format!("{}", offset + 1), v.span());
+						parse_quote! {(#base) + #offset}
+					},
+					None => {
+						let offset = LitInt::new(&format!("{offset}"), v.span());
+						parse_quote! {#offset}
+					},
+				};
+				offset += 1;
+				v.discriminant = Some((Default::default(), expr));
+			},
+		}
+	}
 }
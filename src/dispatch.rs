@@ -0,0 +1,141 @@
+//! Overload resolution used by code generated with `#[interface]`.
+//!
+//! Every overload is described by the [`Hierarchy::MEMBERS`](crate::Hierarchy::MEMBERS) of each of its
+//! polymorphic parameters. An overload is applicable when every argument is a member of the
+//! matching parameter, and it is the most specific when each of its parameters is a subset of the
+//! corresponding parameter of every other applicable overload.
+
+/// Reasons an `#[interface]` function cannot be dispatched.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DispatchError {
+	/// Neither overload is more specific than the other for some combination of arguments.
+	Ambiguous(usize, usize),
+	/// Some combination of arguments has no applicable overload.
+	Uncovered,
+}
+
+const fn contains(set: &[usize], value: usize) -> bool {
+	let mut i = 0;
+	while i < set.len() {
+		if set[i] == value {
+			return true;
+		}
+		i += 1;
+	}
+	false
+}
+
+const fn is_subset(a: &[usize], b: &[usize]) -> bool {
+	let mut i = 0;
+	while i < a.len() {
+		if !contains(b, a[i]) {
+			return false;
+		}
+		i += 1;
+	}
+	true
+}
+
+/// The variant indices of the arguments being dispatched.
+#[derive(Clone, Copy)]
+enum Args<'a> {
+	/// Combination `k` of every possible argument drawn from each parameter.
+	Combination(&'a [&'a [usize]], usize),
+	Exact(&'a [usize]),
+}
+
+impl Args<'_> {
+	const fn get(self, param: usize) -> usize {
+		match self {
+			Args::Combination(params, k) => {
+				let mut stride = 1;
+				let mut i = 0;
+				while i < param {
+					stride *= params[i].len();
+					i += 1;
+				}
+				params[param][(k / stride) % params[param].len()]
+			},
+			Args::Exact(args) => args[param],
+		}
+	}
+}
+
+const fn applicable(overload: &[&[usize]], args: Args) -> bool {
+	let mut j = 0;
+	while j < overload.len() {
+		if !contains(overload[j], args.get(j)) {
+			return false;
+		}
+		j += 1;
+	}
+	true
+}
+
+/// Returns true if every parameter of `a` is a subset of the same parameter of `b`.
+const fn at_least_as_specific(a: &[&[usize]], b: &[&[usize]]) -> bool {
+	let mut j = 0;
+	while j < a.len() {
+		if !is_subset(a[j], b[j]) {
+			return false;
+		}
+		j += 1;
+	}
+	true
+}
+
+const fn resolve(overloads: &[&[&[usize]]], args: Args) -> Result<Option<usize>, DispatchError> {
+	let mut best: Option<usize> = None;
+	let mut i = 0;
+	while i < overloads.len() {
+		if applicable(overloads[i], args) {
+			best = match best {
+				Some(b) if !at_least_as_specific(overloads[i], overloads[b]) => Some(b),
+				Some(b) if at_least_as_specific(overloads[b], overloads[i]) => Some(b),
+				_ => Some(i),
+			};
+		}
+		i += 1;
+	}
+	let Some(b) = best else {
+		return Ok(None);
+	};
+	let mut i = 0;
+	while i < overloads.len() {
+		if i != b && applicable(overloads[i], args) && (
+			!at_least_as_specific(overloads[b], overloads[i]) || at_least_as_specific(overloads[i], overloads[b])
+		) {
+			return Err(if i < b {DispatchError::Ambiguous(i, b)} else {DispatchError::Ambiguous(b, i)});
+		}
+		i += 1;
+	}
+	Ok(Some(b))
+}
+
+/// Checks every combination of arguments drawn from `params` has a single most specific overload.
+///
+/// When `fallback` is true, combinations with no applicable overload are allowed.
+pub const fn check(overloads: &[&[&[usize]]], params: &[&[usize]], fallback: bool) -> Result<(), DispatchError> {
+	let mut combinations = 1;
+	let mut j = 0;
+	while j < params.len() {
+		combinations *= params[j].len();
+		j += 1;
+	}
+	let mut k = 0;
+	while k < combinations {
+		match resolve(overloads, Args::Combination(params, k)) {
+			Ok(Some(_)) => {},
+			Ok(None) if fallback => {},
+			Ok(None) => return Err(DispatchError::Uncovered),
+			Err(err) => return Err(err),
+		}
+		k += 1;
+	}
+	Ok(())
+}
+
+/// Picks the most specific overload for the given argument variant indices.
+pub fn select(overloads: &[&[&[usize]]], args: &[usize]) -> Option<usize> {
+	resolve(overloads, Args::Exact(args)).ok().flatten()
+}
@@ -0,0 +1,78 @@
+use std::{error::Error, fmt};
+
+/// The error returned by a failed [`TryCast`](crate::TryCast), giving back the original value.
+pub struct CastError<T> {
+	value: T,
+	variant: &'static str,
+	target: &'static str,
+	index: Option<usize>,
+}
+
+impl<T> CastError<T> {
+	/// Creates an error for `value`, whose variant `variant` is not a member of `target`
+	pub fn new(value: T, variant: &'static str, target: &'static str) -> Self {
+		Self {value, variant, target, index: None}
+	}
+
+	/// The index of the element that failed to cast, if the value was a collection
+	pub fn index(&self) -> Option<usize> {
+		self.index
+	}
+
+	/// Returns the value that failed to cast
+	pub fn into_inner(self) -> T {
+		self.value
+	}
+
+	/// Replaces the value that failed to cast, keeping the rest of the error
+	pub fn map<U>(self, f: impl FnOnce(T) -> U) -> CastError<U> {
+		CastError {
+			value: f(self.value),
+			variant: self.variant,
+			target: self.target,
+			index: self.index,
+		}
+	}
+
+	/// The name of the enum that could not be cast to
+	pub fn target(&self) -> &'static str {
+		self.target
+	}
+
+	/// The value that failed to cast
+	pub fn value(&self) -> &T {
+		&self.value
+	}
+
+	/// The name of the variant that is not a member of the target
+	pub fn variant(&self) -> &'static str {
+		self.variant
+	}
+
+	/// Records the index of the element that failed to cast
+	pub fn with_index(mut self, index: usize) -> Self {
+		self.index = Some(index);
+		self
+	}
+}
+
+impl<T> fmt::Debug for CastError<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("CastError")
+			.field("variant", &self.variant)
+			.field("target", &self.target)
+			.field("index", &self.index)
+			.finish_non_exhaustive()
+	}
+}
+
+impl<T> fmt::Display for CastError<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if let Some(index) = self.index {
+			write!(f, "element {index}: ")?;
+		}
+		write!(f, "variant `{}` is not a member of `{}`", self.variant, self.target)
+	}
+}
+
+impl<T> Error for CastError<T> {}
@@ -0,0 +1,66 @@
+//! Padding used by `#[poly_enum(layout = shared)]` to give every sub-enum the parent's size and alignment.
+//!
+//! Each sub-enum gains a hidden variant holding a [`Padding`] and an [`Infallible`](std::convert::Infallible), so
+//! the variant can never be constructed but still takes part in the layout.
+
+use std::{cmp::Ordering, fmt, hash::{Hash, Hasher}, mem::MaybeUninit};
+
+use crate::CastCheck;
+
+/// Implemented between enums of a `layout = shared` hierarchy.
+///
+/// # Safety
+/// `Self` and `T` must have the same size and alignment, and every value passing [`CastCheck<T>`] must be a valid `T`.
+pub unsafe trait SharedLayout<T>: CastCheck<T> {}
+
+/// Implemented when every variant of `Self` is a member of `T`.
+///
+/// # Safety
+/// Every value of `Self` must be a valid `T`.
+pub unsafe trait Subset<T>: SharedLayout<T> {}
+
+/// `N` bytes aligned like `T`. Never constructed, it only sits in an uninhabited variant.
+pub struct Padding<T, const N: usize> {
+	_align: [T; 0],
+	_size: [MaybeUninit<u8>; N],
+}
+
+impl<T, const N: usize> Clone for Padding<T, N> {
+	fn clone(&self) -> Self {
+		unreachable!()
+	}
+}
+
+impl<T: Copy, const N: usize> Copy for Padding<T, N> {}
+
+impl<T, const N: usize> fmt::Debug for Padding<T, N> {
+	fn fmt(&self, _f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		unreachable!()
+	}
+}
+
+impl<T, const N: usize> Hash for Padding<T, N> {
+	fn hash<H: Hasher>(&self, _state: &mut H) {
+		unreachable!()
+	}
+}
+
+impl<T, const N: usize> PartialEq for Padding<T, N> {
+	fn eq(&self, _other: &Self) -> bool {
+		unreachable!()
+	}
+}
+
+impl<T, const N: usize> Eq for Padding<T, N> {}
+
+impl<T, const N: usize> PartialOrd for Padding<T, N> {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl<T, const N: usize> Ord for Padding<T, N> {
+	fn cmp(&self, _other: &Self) -> Ordering {
+		unreachable!()
+	}
+}
@@ -3,10 +3,33 @@
 
 use std::{rc::Rc, sync::Arc};
 
+#[doc(hidden)]
+pub mod dispatch;
+mod error;
+#[doc(hidden)]
+pub mod layout;
+
+pub use error::CastError;
+
 /// Generates a hierarchy from the provided enum. Will not work on structs or unions.
 ///
+/// Implements [`Cast`] and [`TryCast`] between the enum and its sub-enums. If the enum has a `repr`, the sub-enums
+/// share it and [`CastRef`] is implemented, otherwise [`CastView`] offers borrowed views of the sub-enums instead.
+/// Sub-enums may be smaller than the parent, so they only implement `Borrow` and `AsRef` to it when no variant has
+/// fields.
+///
+/// The parent, and every sub-enum sharing variants with another, also gets inherent methods for each sub-enum, such as
+/// `is_metal`, `as_metal`, `as_metal_mut` and `into_metal` for a sub-enum named `Metal`. The `as_*` methods exist where
+/// a by-reference cast does. `is_*` agrees with `into_*`, so it also checks any value of the hierarchy nested in the
+/// value. Borrowed views hold nested values as they are though, so there `as_*` only checks the value itself, and can
+/// succeed where `is_*` doesn't.
+///
+/// Every fieldless enum of the hierarchy gets `ALL` and `COUNT` constants and `variant_index`/`from_variant_index`
+/// methods, with its variants in the parent's declaration order.
+///
 /// # #\[poly_derive\]
 /// Pass through derive macros to sub-enums. These do not necessarily need to match the parent's derive attribute.
+/// Derives after a `Sub:` prefix only apply to that sub-enum, and a `!` leaves a derive out.
 /// ```
 /// use poly_enum::PolyEnum;
 /// #[derive(Clone, Copy, Debug, PolyEnum)]
@@ -26,6 +49,62 @@ use std::{rc::Rc, sync::Arc};
 ///     V2(u64, u64),
 /// }
 /// ```
+/// ```
+/// use poly_enum::PolyEnum;
+/// #[derive(Debug, Default, PartialEq, PolyEnum)]
+/// #[poly_derive(Debug, PartialEq, Default, Float: !Default)]
+/// #[poly_derive(Int: Eq, Hash)]
+/// enum Value {
+///     #[poly_enum(Int)]
+///     #[default]
+///     Zero,
+///     #[poly_enum(Int)]
+///     I64(i64),
+///     #[poly_enum(Float)]
+///     F64(f64),
+/// }
+///
+/// assert_eq!(Int::default(), Int::Zero);
+/// ```
+///
+/// # #\[poly_attr\] and #\[poly_vis\]
+/// Give a single sub-enum extra attributes, such as its docs, or its own visibility. Sub-enums are otherwise documented
+/// as a sub-enum of the parent, and have its visibility, lint levels, `non_exhaustive` and `must_use`. They share the
+/// parent's layout, so they can't be given their own `repr`.
+/// ```
+/// use poly_enum::PolyEnum;
+/// #[derive(PolyEnum)]
+/// #[poly_attr(Int, doc = "Whole numbers", derive(Clone, Copy))]
+/// #[poly_vis(Float, pub(crate))]
+/// pub enum Value {
+///     #[poly_enum(Int)]
+///     I64(i64),
+///     #[poly_enum(Float)]
+///     F64(f64),
+/// }
+/// ```
+///
+/// Attributes of variants and fields are copied to every sub-enum, while `#[poly_attr(..)]` on them gives attributes
+/// to a single sub-enum, or to the parent with `Self`. This derive can't change the parent, so its attributes are
+/// written as usual as well, and `#[poly_attr(Self, ..)]` only keeps them out of the sub-enums.
+/// ```
+/// use poly_enum::PolyEnum;
+/// #[derive(Debug, Default, PartialEq, PolyEnum)]
+/// #[poly_derive(Debug, Default, PartialEq)]
+/// enum Value {
+///     #[poly_enum(Int)]
+///     #[default]
+///     #[poly_attr(Self, default)]
+///     Empty,
+///     #[poly_enum(Int)]
+///     #[poly_attr(Int, default)]
+///     Zero,
+///     Text(String),
+/// }
+///
+/// assert_eq!(Value::default(), Value::Empty);
+/// assert_eq!(Int::default(), Int::Zero);
+/// ```
 ///
 /// # #\[poly_enum\]
 /// Mark an enum variant as belonging to one or more sub-enums.
@@ -49,15 +128,229 @@ use std::{rc::Rc, sync::Arc};
 /// ```
 pub use poly_enum_derive::PolyEnum;
 
+/// Generates a hierarchy from the provided enum, which must have a `repr`, or `#[poly_enum(repr = auto)]` to insert
+/// the smallest primitive that fits its discriminants.
+///
+/// Every sub-enum shares the `repr`, including `C`, `align` and stacked attributes, and the parent's discriminants,
+/// explicit or implicit, so [`CastRef`] can reinterpret references. Sub-enums can be smaller than the parent though, so
+/// they only implement `Borrow` and `AsRef` to it when no variant has fields, or with `layout = shared`, and otherwise
+/// convert to it with `From` alone. `layout = shared` pads every sub-enum to the parent's size and alignment with a
+/// hidden, uninhabited variant, which matches through a reference must cover with a wildcard arm. Pointers and slices
+/// can then be cast in place with [`CastPtr`], [`UpcastPtr`], [`CastSlice`] and [`CastSliceMut`].
+///
+/// A nested `Self` reached through a cast reference would be cloned or dropped with the layout of another enum, so
+/// without `layout = shared` the sub-enums of variants containing `Self` are borrowed through views, like those of
+/// [`PolyEnum`], and don't implement [`CastRef`]. The same goes for sub-enums with fields under `repr(C)`, whose
+/// fields follow a union of every variant and so may move.
+///
+/// Like [`PolyEnum`], inherent `is_*`, `as_*`, `as_*_mut` and `into_*` methods are generated for every sub-enum.
+///
+/// The parent's derives are copied to every sub-enum, and `#[poly_derive(..)]` adjusts them as it does for
+/// [`PolyEnum`], eg `#[poly_derive(!Default)]` or `#[poly_derive(Int: Hash, Eq)]`. Every other attribute of the parent
+/// but its docs is copied too, and `#[poly_attr(..)]` and `#[poly_vis(..)]` work as they do for [`PolyEnum`], except that
+/// `#[poly_attr(Self, ..)]` on a variant or field gives the parent attributes that needn't be written as usual.
+/// ```
+/// use std::borrow::Borrow;
+/// #[poly_enum::poly_enum(layout = shared)]
+/// #[repr(u8)]
+/// #[derive(Debug, PartialEq)]
+/// enum Value {
+///     #[poly_enum(Small)]
+///     U8(u8),
+///     U64(u64),
+/// }
+///
+/// assert_eq!(std::mem::size_of::<Small>(), std::mem::size_of::<Value>());
+/// let value: &Value = Small::U8(1).borrow();
+/// assert_eq!(value, &Value::U8(1));
+/// ```
+///
+/// `{Enum}::partition` splits values into a generated `{Enum}Partition`, with a `Vec` per sub-enum and a `remainder`.
+/// A value in several sub-enums goes into the first of them by priority, which is the order sub-enums are first
+/// mentioned unless listed with `partition(..)`. With `partition = clone` it is cloned into every one instead.
+/// ```
+/// #[poly_enum::poly_enum(partition(Alkali))]
+/// #[repr(u8)]
+/// #[derive(Debug, PartialEq)]
+/// enum Elements {
+///     #[poly_enum(Metal)]
+///     Iron,
+///     #[poly_enum(Metal, Alkali)]
+///     Sodium,
+///     Argon,
+/// }
+///
+/// let partition = Elements::partition([Elements::Iron, Elements::Sodium, Elements::Argon]);
+/// assert_eq!(partition.metal, [Metal::Iron]);
+/// assert_eq!(partition.alkali, [Alkali::Sodium]);
+/// assert_eq!(partition.remainder, [Elements::Argon]);
+/// ```
+///
+/// So no sub-enum can be named `Remainder`.
+///
+/// Sub-enums are declared next to the parent unless `module = name` declares them, and their views, in a module of
+/// that name with the parent's visibility. Field types are then resolved in the module, which imports everything from
+/// the parent's. `prefix = Name` and `suffix = Name` rename them instead, keeping the names of the inherent methods.
+/// ```
+/// #[poly_enum::poly_enum(module = value)]
+/// #[repr(u8)]
+/// #[derive(Debug, PartialEq)]
+/// enum Value {
+///     #[poly_enum(Int)]
+///     I64(i64),
+///     F64(f64),
+/// }
+///
+/// assert_eq!(Value::I64(1).into_int(), Ok(value::Int::I64(1)));
+/// ```
+///
+/// Array lengths can't depend on generic parameters, so enums generic over types can't be padded, and asking for it is
+/// an error.
+///
+/// Other mistakes in the declaration are reported at the offending span too:
+/// - a sub-enum named like the parent, another generated item or a borrowed view;
+/// - a sub-enum listed twice on one variant, or given as a path rather than a name;
+/// - a sub-enum with every variant, which would be a copy of the parent;
+/// - a `repr` too small to tell every variant apart, including the padding of `layout = shared`;
+/// - `Self` held other than directly or in a `Box`, `Rc`, `Arc` or `Vec`, as casting needs to move it between enums.
+///
+/// Two sub-enums with the same variants are copies of each other. They are still generated and convert into each
+/// other with `From`, but a warning points at the second. It is raised as a deprecation, so `#[allow(deprecated)]` on
+/// the parent silences it.
+/// ```
+/// #[poly_enum::poly_enum]
+/// #[repr(u8)]
+/// #[derive(Debug, PartialEq)]
+/// #[allow(deprecated)]
+/// enum Elements {
+///     #[poly_enum(Metal, Magnetic)]
+///     Iron,
+///     Carbon,
+/// }
+///
+/// assert_eq!(Metal::from(Magnetic::Iron), Metal::Iron);
+/// assert_eq!(Magnetic::from(Metal::Iron), Magnetic::Iron);
+/// ```
+///
+/// Mistakes like these are reported alongside the parent and every sub-enum that could still be generated, so the
+/// rest of the crate keeps their types. Each sub-enum is named by the first `#[poly_enum(..)]` mentioning it, which is
+/// where an IDE goes to its definition.
 pub use poly_enum_derive::poly_enum;
 
+/// Turns a function into a multiple dispatch interface over a hierarchy.
+///
+/// Functions nested in the body with the same name are overloads. When called, the most specific
+/// overload for the variants of the arguments is chosen and the arguments are cast to its parameter
+/// types. Parameters can be taken by value, `&` or `&mut`. Any statements left in the body are used
+/// as a fallback when no overload applies.
+/// ```
+/// #[poly_enum::poly_enum]
+/// #[repr(u32)]
+/// enum Elements {
+///     #[poly_enum(NonMetal)]
+///     Carbon,
+///     #[poly_enum(Metal)]
+///     Iron,
+///     #[poly_enum(Alkali, Metal)]
+///     Sodium,
+/// }
+///
+/// #[poly_enum::interface]
+/// fn describe(e: Elements) -> &'static str {
+///     fn describe(_m: Metal) -> &'static str {"metal"}
+///     fn describe(_a: Alkali) -> &'static str {"alkali"}
+///     "something else"
+/// }
+///
+/// assert_eq!(describe(Elements::Carbon), "something else");
+/// assert_eq!(describe(Elements::Iron), "metal");
+/// assert_eq!(describe(Elements::Sodium), "alkali");
+/// ```
+///
+/// Overloads that are equally specific for some arguments are rejected at compile time.
+/// ```compile_fail
+/// #[poly_enum::poly_enum]
+/// #[repr(u32)]
+/// enum Elements {
+///     #[poly_enum(Metal)]
+///     Iron,
+///     #[poly_enum(Alkali, Metal)]
+///     Sodium,
+/// }
+///
+/// #[poly_enum::interface]
+/// fn react(a: Elements, b: Elements) -> bool {
+///     fn react(_a: Metal, _b: Alkali) -> bool {true}
+///     fn react(_a: Alkali, _b: Metal) -> bool {false}
+///     false
+/// }
+/// ```
+///
+/// Without a fallback, the overloads must cover every variant.
+/// ```compile_fail
+/// #[poly_enum::poly_enum]
+/// #[repr(u32)]
+/// enum Elements {
+///     #[poly_enum(NonMetal)]
+///     Carbon,
+///     #[poly_enum(Metal)]
+///     Iron,
+/// }
+///
+/// #[poly_enum::interface]
+/// fn describe(e: Elements) -> &'static str {
+///     fn describe(_m: Metal) -> &'static str {"metal"}
+/// }
+/// ```
+pub use poly_enum_derive::interface;
+
+/// Describes where an enum sits within its hierarchy.
+/// Implemented for the parent enum and every sub-enum.
+pub trait Hierarchy {
+	/// Indices of the parent's variants contained in this enum, in declaration order
+	const MEMBERS: &'static [usize];
+	/// Index of this value's variant within the parent enum
+	fn member_index(&self) -> usize;
+	/// Name of this value's variant
+	fn variant_name(&self) -> &'static str;
+}
+
 /// This trait allows casting between different enums within a hierarchy.
 pub trait Cast<T> {
 	/// Attempts to cast from one enum to another
 	fn cast(self) -> Option<T>;
 }
 
+/// This trait allows casting between different enums within a hierarchy, giving back the value on failure.
+/// ```
+/// use poly_enum::TryCast;
+/// #[poly_enum::poly_enum]
+/// #[repr(u32)]
+/// #[derive(Debug)]
+/// enum Value {
+///     #[poly_enum(Text)]
+///     String(String),
+///     #[poly_enum(Number)]
+///     I64(i64),
+/// }
+///
+/// let err = TryCast::<Number>::try_cast(Value::String("ten".to_string())).unwrap_err();
+/// assert_eq!(err.variant(), "String");
+/// assert_eq!(err.target(), "Number");
+/// assert!(matches!(err.into_inner(), Value::String(s) if s == "ten"));
+/// ```
+pub trait TryCast<T>: Sized {
+	/// Attempts to cast from one enum to another
+	fn try_cast(self) -> Result<T, CastError<Self>>;
+}
+
 /// This trait allows casting between different enums within a hierarchy.
+///
+/// Implemented from the parent to each sub-enum, and between sub-enums where every variant of the target is a member
+/// of the source. Otherwise a value written through `cast_mut` might not be valid in the source.
+///
+/// Variants containing `Self` only cast when every value nested in them is a member too, see [`CastCheck`], and
+/// only between enums of a `layout = shared` hierarchy.
 pub trait CastRef<T> {
 	/// Attempts to cast from one enum to another by mut ref
 	fn cast_mut(&mut self) -> Option<&mut T>;
@@ -65,14 +358,283 @@ pub trait CastRef<T> {
 	fn cast_ref(&self) -> Option<&T>;
 }
 
-/// The main trait for polymorphic enums.
-/// This trait allows casting between different enums within a hierarchy.
-#[deprecated]
-pub trait PolyEnum<T> {
-	/// Attempts to cast from one enum to another
-	fn cast(self) -> Option<T>;
+/// Checks whether a value, and every value of the hierarchy nested inside it, is a member of `T`.
+///
+/// This is what lets [`CastRef`] reinterpret variants containing `Self`, whose nested values must be members too, and
+/// [`TryCast`] give back a `Box`, `Rc`, `Arc` or `Vec` untouched when it fails.
+pub trait CastCheck<T: ?Sized> {
+	/// Returns the error a [`TryCast`] to `T` would fail with, without the value
+	fn check_cast(&self) -> Result<(), CastError<()>>;
+
+	/// Returns true if the value can be viewed as `T`
+	fn can_cast(&self) -> bool {
+		self.check_cast().is_ok()
+	}
 }
 
+impl<T, U> CastCheck<Arc<U>> for Arc<T> where T: CastCheck<U> {
+	fn check_cast(&self) -> Result<(), CastError<()>> {
+		T::check_cast(self)
+	}
+}
+
+impl<T, U> CastCheck<Box<U>> for Box<T> where T: CastCheck<U> {
+	fn check_cast(&self) -> Result<(), CastError<()>> {
+		T::check_cast(self)
+	}
+}
+
+impl<T, U> CastCheck<Rc<U>> for Rc<T> where T: CastCheck<U> {
+	fn check_cast(&self) -> Result<(), CastError<()>> {
+		T::check_cast(self)
+	}
+}
+
+impl<T, U> CastCheck<Vec<U>> for Vec<T> where T: CastCheck<U> {
+	fn check_cast(&self) -> Result<(), CastError<()>> {
+		self.iter().enumerate().try_for_each(|(index, e)| e.check_cast().map_err(|err| err.with_index(index)))
+	}
+}
+
+/// Casts `Box`, `Rc`, `Arc` and `Vec` between enums of a `layout = shared` hierarchy, reusing the allocation.
+///
+/// Only the discriminants, and those of any nested values, are checked. Shared pointers stay shared and nothing is
+/// cloned.
+/// ```
+/// use std::rc::Rc;
+/// use poly_enum::{CastPtr, UpcastPtr};
+/// #[poly_enum::poly_enum(layout = shared)]
+/// #[repr(u8)]
+/// enum Elements {
+///     #[poly_enum(Metal)]
+///     Iron(u32),
+///     Oxygen(u64),
+/// }
+///
+/// let elements = Rc::new(Elements::Iron(26));
+/// let metal: Rc<Metal> = elements.clone().cast_ptr().ok().unwrap();
+/// assert_eq!(Rc::strong_count(&metal), 2);
+/// let elements: Rc<Elements> = metal.upcast_ptr();
+/// assert!(matches!(*elements, Elements::Iron(26)));
+/// ```
+pub trait CastPtr<T>: Sized {
+	/// Attempts to cast the pointee in place, giving back the pointer if it isn't a member of the target
+	fn cast_ptr(self) -> Result<T, Self>;
+}
+
+impl<T, U> CastPtr<Arc<U>> for Arc<T> where T: layout::SharedLayout<U> {
+	fn cast_ptr(self) -> Result<Arc<U>, Self> {
+		if !T::can_cast(&self) {
+			return Err(self);
+		}
+		Ok(unsafe {Arc::from_raw(Arc::into_raw(self) as *const U)})
+	}
+}
+
+impl<T, U> CastPtr<Box<U>> for Box<T> where T: layout::SharedLayout<U> {
+	fn cast_ptr(self) -> Result<Box<U>, Self> {
+		if !T::can_cast(&self) {
+			return Err(self);
+		}
+		Ok(unsafe {Box::from_raw(Box::into_raw(self) as *mut U)})
+	}
+}
+
+impl<T, U> CastPtr<Rc<U>> for Rc<T> where T: layout::SharedLayout<U> {
+	fn cast_ptr(self) -> Result<Rc<U>, Self> {
+		if !T::can_cast(&self) {
+			return Err(self);
+		}
+		Ok(unsafe {Rc::from_raw(Rc::into_raw(self) as *const U)})
+	}
+}
+
+/// Every element is checked before the buffer is reused, so a failed cast gives back the vector unchanged.
+impl<T, U> CastPtr<Vec<U>> for Vec<T> where T: layout::SharedLayout<U> {
+	fn cast_ptr(self) -> Result<Vec<U>, Self> {
+		if !self.iter().all(T::can_cast) {
+			return Err(self);
+		}
+		let mut vec = std::mem::ManuallyDrop::new(self);
+		Ok(unsafe {Vec::from_raw_parts(vec.as_mut_ptr() as *mut U, vec.len(), vec.capacity())})
+	}
+}
+
+/// Casts slices between enums of a `layout = shared` hierarchy without copying.
+///
+/// Every element is checked in one pass before the slice is reinterpreted.
+/// ```
+/// use poly_enum::{CastSlice, CastSliceMut};
+/// #[poly_enum::poly_enum(layout = shared)]
+/// #[repr(u8)]
+/// #[derive(Debug, PartialEq)]
+/// enum Elements {
+///     #[poly_enum(Metal)]
+///     Iron(u32),
+///     Oxygen(u64),
+/// }
+///
+/// let mut elements = [Elements::Iron(26), Elements::Iron(1)];
+/// let metals: &mut [Metal] = elements.cast_slice_mut().unwrap();
+/// metals[1] = Metal::Iron(2);
+/// let elements: &[Elements] = metals.cast_slice().unwrap();
+/// assert_eq!(elements, [Elements::Iron(26), Elements::Iron(2)]);
+/// assert!(CastSlice::<Metal>::cast_slice(&[Elements::Oxygen(8)][..]).is_none());
+/// ```
+pub trait CastSlice<T> {
+	/// Attempts to view every element as `T`
+	fn cast_slice(&self) -> Option<&[T]>;
+}
+
+impl<T, U> CastSlice<U> for [T] where T: layout::SharedLayout<U> {
+	fn cast_slice(&self) -> Option<&[U]> {
+		self.iter().all(T::can_cast).then(|| unsafe {std::slice::from_raw_parts(self.as_ptr() as *const U, self.len())})
+	}
+}
+
+/// Mutably casts slices between enums of a `layout = shared` hierarchy without copying.
+///
+/// Like [`CastRef`], only implemented where every value of the target is valid in the source.
+pub trait CastSliceMut<T>: CastSlice<T> {
+	/// Attempts to mutably view every element as `T`
+	fn cast_slice_mut(&mut self) -> Option<&mut [T]>;
+}
+
+impl<T, U> CastSliceMut<U> for [T] where T: layout::SharedLayout<U> + CastRef<U> {
+	fn cast_slice_mut(&mut self) -> Option<&mut [U]> {
+		self.iter().all(T::can_cast).then(|| unsafe {std::slice::from_raw_parts_mut(self.as_mut_ptr() as *mut U, self.len())})
+	}
+}
+
+/// Widens `Box`, `Rc`, `Arc` and `Vec` to an enum containing every variant of the pointee, reusing the allocation.
+///
+/// See [`CastPtr`] for narrowing casts.
+pub trait UpcastPtr<T> {
+	/// Casts the pointee in place
+	fn upcast_ptr(self) -> T;
+}
+
+impl<T, U> UpcastPtr<Arc<U>> for Arc<T> where T: layout::Subset<U> {
+	fn upcast_ptr(self) -> Arc<U> {
+		unsafe {Arc::from_raw(Arc::into_raw(self) as *const U)}
+	}
+}
+
+impl<T, U> UpcastPtr<Box<U>> for Box<T> where T: layout::Subset<U> {
+	fn upcast_ptr(self) -> Box<U> {
+		unsafe {Box::from_raw(Box::into_raw(self) as *mut U)}
+	}
+}
+
+impl<T, U> UpcastPtr<Rc<U>> for Rc<T> where T: layout::Subset<U> {
+	fn upcast_ptr(self) -> Rc<U> {
+		unsafe {Rc::from_raw(Rc::into_raw(self) as *const U)}
+	}
+}
+
+impl<T, U> UpcastPtr<Vec<U>> for Vec<T> where T: layout::Subset<U> {
+	fn upcast_ptr(self) -> Vec<U> {
+		let mut vec = std::mem::ManuallyDrop::new(self);
+		unsafe {Vec::from_raw_parts(vec.as_mut_ptr() as *mut U, vec.len(), vec.capacity())}
+	}
+}
+
+/// By-reference casting for hierarchies whose sub-enums don't share the parent's layout.
+///
+/// `#[derive(PolyEnum)]` on an enum without a `repr` generates a `{Sub}Ref` and `{Sub}Mut` enum for each sub-enum,
+/// whose variants borrow the parent's fields.
+/// ```
+/// use poly_enum::{CastView, PolyEnum};
+/// #[derive(PolyEnum)]
+/// enum Value {
+///     #[poly_enum(Int)]
+///     I64(i64),
+///     #[poly_enum(Int)]
+///     U64(u64),
+///     F64(f64),
+/// }
+///
+/// let mut value = Value::I64(1);
+/// if let Some(IntMut::I64(v)) = CastView::<Int>::cast_view_mut(&mut value) {
+///     *v += 1;
+/// }
+/// assert!(matches!(CastView::<Int>::cast_view(&value), Some(IntRef::I64(2))));
+/// ```
+pub trait CastView<T> {
+	/// A borrowed view of the sub-enum
+	type Ref<'a> where Self: 'a;
+	/// A mutably borrowed view of the sub-enum
+	type Mut<'a> where Self: 'a;
+	/// Attempts to borrow the value as a view of the sub-enum
+	fn cast_view(&self) -> Option<Self::Ref<'_>>;
+	/// Attempts to mutably borrow the value as a view of the sub-enum
+	fn cast_view_mut(&mut self) -> Option<Self::Mut<'_>>;
+}
+
+/// Borrows a value as `T` through [`CastRef`], keeping the original lifetime. Used by [`PolyIterExt::filter_cast_ref`].
+#[doc(hidden)]
+pub trait CastRefItem<'a, T: 'a> {
+	/// Attempts to cast from one enum to another by ref
+	fn cast_ref_item(self) -> Option<&'a T>;
+}
+
+impl<'a, S, T: 'a> CastRefItem<'a, T> for &'a S where S: CastRef<T> + ?Sized {
+	fn cast_ref_item(self) -> Option<&'a T> {
+		self.cast_ref()
+	}
+}
+
+/// Iterator adapters for casting the items of a hierarchy.
+/// ```
+/// use poly_enum::PolyIterExt;
+/// #[poly_enum::poly_enum]
+/// #[repr(u32)]
+/// #[derive(Debug, PartialEq)]
+/// enum Elements {
+///     #[poly_enum(NonMetal)]
+///     Carbon,
+///     #[poly_enum(Metal)]
+///     Iron,
+///     #[poly_enum(Metal)]
+///     Sodium,
+/// }
+///
+/// let elements = vec![Elements::Carbon, Elements::Iron, Elements::Sodium];
+/// let metals = elements.iter().filter_cast_ref::<Metal>().collect::<Vec<_>>();
+/// assert_eq!(metals, [&Metal::Iron, &Metal::Sodium]);
+///
+/// let err = elements.into_iter().try_cast_all::<Metal>().unwrap_err();
+/// assert_eq!(err.index(), Some(0));
+/// ```
+pub trait PolyIterExt: Iterator + Sized {
+	/// Yields the items that cast to `T`, skipping the rest
+	fn filter_cast<T>(self) -> impl Iterator<Item = T> where Self::Item: Cast<T> {
+		self.filter_map(Cast::cast)
+	}
+
+	/// Yields the borrowed items that cast to `&T` by reference, skipping the rest
+	fn filter_cast_ref<'a, T: 'a>(self) -> impl Iterator<Item = &'a T> where Self::Item: CastRefItem<'a, T> {
+		self.filter_map(CastRefItem::cast_ref_item)
+	}
+
+	/// Casts every item to `T`, stopping at the first that fails and recording its index in the error
+	fn try_cast_all<T>(self) -> Result<Vec<T>, CastError<Self::Item>> where Self::Item: TryCast<T> {
+		self.enumerate().map(|(index, item)| item.try_cast().map_err(|err| err.with_index(index))).collect()
+	}
+
+	/// Widens every item to `T`
+	fn upcast<T>(self) -> impl Iterator<Item = T> where Self::Item: Into<T> {
+		self.map(Into::into)
+	}
+}
+
+impl<I: Iterator> PolyIterExt for I {}
+
+/// The former name of [`Cast`], kept so that existing imports and calls still work.
+///
+/// It is the same trait, so importing both, eg through `use poly_enum::*;`, doesn't make `cast` ambiguous.
+pub use Cast as PolyEnum;
+
 impl<T, U> Cast<Arc<U>> for Arc<T> where T: Clone + Cast<U> {
 	fn cast(self) -> Option<Arc<U>> {
 		Arc::unwrap_or_clone(self).cast().map(Arc::new)
@@ -91,36 +653,49 @@ impl<T, U> Cast<Rc<U>> for Rc<T> where T: Clone + Cast<U> {
 	}
 }
 
+/// Collects into a new vector, see [`CastPtr`] to reuse the buffer of a `layout = shared` hierarchy.
 impl<T, U> Cast<Vec<U>> for Vec<T> where T: Cast<U> {
 	fn cast(self) -> Option<Vec<U>> {
 		self.into_iter().map(|e| e.cast()).collect::<Option<Vec<_>>>()
 	}
 }
 
-#[allow(deprecated)]
-impl<T, U> PolyEnum<Arc<U>> for Arc<T> where T: Clone + PolyEnum<U> {
-	fn cast(self) -> Option<Arc<U>> {
-		Arc::unwrap_or_clone(self).cast().map(Arc::new)
+/// The value is checked by reference first, so on failure the original `Arc` is given back untouched.
+impl<T, U> TryCast<Arc<U>> for Arc<T> where T: Clone + TryCast<U> + CastCheck<U> {
+	fn try_cast(self) -> Result<Arc<U>, CastError<Self>> {
+		if let Err(err) = T::check_cast(&self) {
+			return Err(err.map(|()| self));
+		}
+		Arc::unwrap_or_clone(self).try_cast().map(Arc::new).map_err(|err| err.map(Arc::new))
 	}
 }
 
-#[allow(deprecated)]
-impl<T, U> PolyEnum<Box<U>> for Box<T> where T: PolyEnum<U> {
-	fn cast(self) -> Option<Box<U>> {
-		(*self).cast().map(Box::new)
+/// The value is checked by reference first, so on failure the original `Box` is given back untouched.
+impl<T, U> TryCast<Box<U>> for Box<T> where T: TryCast<U> + CastCheck<U> {
+	fn try_cast(self) -> Result<Box<U>, CastError<Self>> {
+		if let Err(err) = T::check_cast(&self) {
+			return Err(err.map(|()| self));
+		}
+		(*self).try_cast().map(Box::new).map_err(|err| err.map(Box::new))
 	}
 }
 
-#[allow(deprecated)]
-impl<T, U> PolyEnum<Rc<U>> for Rc<T> where T: Clone + PolyEnum<U> {
-	fn cast(self) -> Option<Rc<U>> {
-		Rc::unwrap_or_clone(self).cast().map(Rc::new)
+/// The value is checked by reference first, so on failure the original `Rc` is given back untouched.
+impl<T, U> TryCast<Rc<U>> for Rc<T> where T: Clone + TryCast<U> + CastCheck<U> {
+	fn try_cast(self) -> Result<Rc<U>, CastError<Self>> {
+		if let Err(err) = T::check_cast(&self) {
+			return Err(err.map(|()| self));
+		}
+		Rc::unwrap_or_clone(self).try_cast().map(Rc::new).map_err(|err| err.map(Rc::new))
 	}
 }
 
-#[allow(deprecated)]
-impl<T, U> PolyEnum<Vec<U>> for Vec<T> where T: PolyEnum<U> {
-	fn cast(self) -> Option<Vec<U>> {
-		self.into_iter().map(|e| e.cast()).collect::<Option<Vec<_>>>()
+/// Every element is checked by reference first, so on failure the original vector is given back untouched.
+impl<T, U> TryCast<Vec<U>> for Vec<T> where T: TryCast<U> + CastCheck<U> {
+	fn try_cast(self) -> Result<Vec<U>, CastError<Self>> {
+		if let Err(err) = CastCheck::<Vec<U>>::check_cast(&self) {
+			return Err(err.map(|()| self));
+		}
+		Ok(self.into_iter().map(|e| e.try_cast().unwrap_or_else(|_| unreachable!())).collect())
 	}
 }
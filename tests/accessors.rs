@@ -0,0 +1,96 @@
+#[poly_enum::poly_enum(layout = shared)]
+#[repr(u32)]
+#[derive(Debug, PartialEq)]
+enum Elements {
+	#[poly_enum(NonMetal)]
+	Carbon,
+	#[poly_enum(Oxidizer, NonMetal)]
+	Oxygen(u8),
+	#[poly_enum(Metal)]
+	Iron(u64),
+	#[poly_enum(Alkali, Metal)]
+	Sodium,
+	#[poly_enum(Oxidizer, Metal)]
+	Mixture(Box<Self>),
+}
+
+#[derive(Debug, PartialEq, poly_enum::PolyEnum)]
+#[poly_derive(Debug, PartialEq)]
+enum Value {
+	#[poly_enum(Text)]
+	String(String),
+	#[poly_enum(Number)]
+	I64(i64),
+}
+
+// Without a `repr`, nested values are only checked, so the sizes needn't match
+#[derive(poly_enum::PolyEnum)]
+enum Tree {
+	#[poly_enum(Seq)]
+	List(Vec<Self>),
+	Big([u64; 8]),
+}
+
+#[test]
+fn parent() {
+	let mut iron = Elements::Iron(26);
+	assert!(iron.is_metal());
+	assert!(!iron.is_non_metal());
+	assert_eq!(iron.as_metal(), Some(&Metal::Iron(26)));
+	assert_eq!(iron.as_alkali(), None);
+	if let Some(Metal::Iron(n)) = iron.as_metal_mut() {
+		*n = 27;
+	}
+	assert_eq!(iron.into_non_metal(), Err(Elements::Iron(27)));
+	assert_eq!(Elements::Sodium.into_alkali(), Ok(Alkali::Sodium));
+}
+
+#[test]
+fn nested_values() {
+	let mixture = Elements::Mixture(Box::new(Elements::Carbon));
+	assert!(!mixture.is_metal());
+	assert_eq!(mixture.as_metal(), None);
+	let mixture = Elements::Mixture(Box::new(Elements::Sodium));
+	assert!(mixture.is_metal());
+	assert!(!mixture.is_oxidizer());
+}
+
+#[test]
+fn overlapping_sub_enums() {
+	let mut metal = Metal::Sodium;
+	assert!(metal.is_alkali());
+	assert!(!metal.is_oxidizer());
+	assert_eq!(metal.as_alkali(), Some(&Alkali::Sodium));
+	assert_eq!(metal.as_alkali_mut(), Some(&mut Alkali::Sodium));
+	assert_eq!(metal.into_oxidizer(), Err(Metal::Sodium));
+
+	let alkali = Alkali::Sodium;
+	assert!(alkali.is_metal());
+	assert_eq!(alkali.into_metal(), Ok(Metal::Sodium));
+	assert_eq!(NonMetal::Oxygen(8).into_oxidizer(), Ok(Oxidizer::Oxygen(8)));
+}
+
+#[test]
+fn views() {
+	let mut value = Value::String("a".to_string());
+	assert!(value.is_text());
+	assert!(matches!(value.as_text(), Some(TextRef::String(s)) if s == "a"));
+	if let Some(TextMut::String(s)) = value.as_text_mut() {
+		s.push('b');
+	}
+	assert!(value.as_number().is_none());
+	assert_eq!(value.into_text(), Ok(Text::String("ab".to_string())));
+}
+
+#[test]
+fn nested_without_layout() {
+	assert!(Tree::List(vec![Tree::List(Vec::new())]).is_seq());
+	assert!(!Tree::List(vec![Tree::Big([0; 8])]).is_seq());
+	assert!(matches!(Tree::List(Vec::new()).as_seq(), Some(SeqRef::List(list)) if list.is_empty()));
+	// Views hold nested values as they are, so only `is_seq` and `into_seq` look at them
+	let tree = Tree::List(vec![Tree::Big([2; 8])]);
+	assert!(!tree.is_seq());
+	assert!(tree.as_seq().is_some());
+	assert!(tree.into_seq().is_err());
+	assert!(matches!(Tree::Big([1; 8]).into_seq(), Err(Tree::Big(big)) if big[7] == 1));
+}
@@ -0,0 +1,85 @@
+use std::{rc::Rc, sync::Arc};
+
+use poly_enum::{CastPtr, UpcastPtr};
+
+#[poly_enum::poly_enum(layout = shared)]
+#[repr(u32)]
+#[derive(Debug, PartialEq)]
+enum Elements {
+	#[poly_enum(NonMetal)]
+	Carbon(u8),
+	#[poly_enum(Metal)]
+	Iron(u64),
+	#[poly_enum(Alkali, Metal)]
+	Sodium,
+	#[poly_enum(NonMetal)]
+	Compound(Box<Self>, Box<Self>),
+}
+
+/// Not `Clone`, so the pointers can't have been cast by cloning the value
+#[test]
+fn rc_keeps_sharing() {
+	let elements = Rc::new(Elements::Iron(26));
+	let ptr = Rc::as_ptr(&elements) as *const u8;
+	let metal: Rc<Metal> = elements.clone().cast_ptr().unwrap();
+	assert_eq!(Rc::strong_count(&elements), 2);
+	assert_eq!(Rc::as_ptr(&metal) as *const u8, ptr);
+	assert_eq!(*metal, Metal::Iron(26));
+
+	let widened: Rc<Elements> = metal.upcast_ptr();
+	assert!(Rc::ptr_eq(&widened, &elements));
+
+	let elements = CastPtr::<Rc<NonMetal>>::cast_ptr(elements).unwrap_err();
+	assert_eq!(Rc::strong_count(&elements), 2);
+}
+
+#[test]
+fn arc_between_sub_enums() {
+	let metal = Arc::new(Metal::Sodium);
+	let ptr = Arc::as_ptr(&metal) as *const u8;
+	let alkali: Arc<Alkali> = metal.cast_ptr().unwrap();
+	assert_eq!(Arc::as_ptr(&alkali) as *const u8, ptr);
+	let metal: Arc<Metal> = alkali.upcast_ptr();
+	assert!(CastPtr::<Arc<NonMetal>>::cast_ptr(metal).is_err());
+}
+
+#[test]
+fn box_checks_nested_values() {
+	let compound = Box::new(Elements::Compound(Box::new(Elements::Carbon(6)), Box::new(Elements::Carbon(6))));
+	let ptr = &*compound as *const Elements as *const u8;
+	let non_metal: Box<NonMetal> = compound.cast_ptr().unwrap();
+	assert_eq!(&*non_metal as *const NonMetal as *const u8, ptr);
+	assert_eq!(*non_metal, NonMetal::Compound(Box::new(NonMetal::Carbon(6)), Box::new(NonMetal::Carbon(6))));
+
+	let compound = Box::new(Elements::Compound(Box::new(Elements::Carbon(6)), Box::new(Elements::Iron(26))));
+	let compound = CastPtr::<Box<NonMetal>>::cast_ptr(compound).unwrap_err();
+	let _: Box<Elements> = UpcastPtr::<Box<Elements>>::upcast_ptr(Box::new(Metal::Iron(1)));
+	drop(compound);
+}
+
+#[test]
+fn vec_reuses_buffer() {
+	let mut metals = Vec::with_capacity(1000);
+	metals.extend((0..600).map(|i| if i % 2 == 0 {Metal::Iron(i)} else {Metal::Sodium}));
+	let (ptr, capacity) = (metals.as_ptr() as *const u8, metals.capacity());
+
+	let elements: Vec<Elements> = metals.upcast_ptr();
+	assert_eq!((elements.as_ptr() as *const u8, elements.capacity()), (ptr, capacity));
+	assert_eq!(elements.len(), 600);
+	assert_eq!(elements[598], Elements::Iron(598));
+
+	let metals: Vec<Metal> = elements.cast_ptr().unwrap();
+	assert_eq!((metals.as_ptr() as *const u8, metals.capacity()), (ptr, capacity));
+	assert_eq!(metals[599], Metal::Sodium);
+}
+
+#[test]
+fn vec_failure_is_unchanged() {
+	let mut elements = Vec::with_capacity(10);
+	elements.extend([Elements::Sodium, Elements::Iron(26), Elements::Carbon(6)]);
+	let (ptr, capacity) = (elements.as_ptr(), elements.capacity());
+
+	let elements = CastPtr::<Vec<Metal>>::cast_ptr(elements).unwrap_err();
+	assert_eq!((elements.as_ptr(), elements.capacity()), (ptr, capacity));
+	assert_eq!(elements, [Elements::Sodium, Elements::Iron(26), Elements::Carbon(6)]);
+}
@@ -0,0 +1,81 @@
+use poly_enum::CastRef;
+
+#[poly_enum::poly_enum(layout = shared)]
+#[repr(u8)]
+#[derive(Debug, PartialEq)]
+enum Expr {
+	#[poly_enum(Pure, Num)]
+	Lit(i64),
+	#[poly_enum(Pure)]
+	Add(Box<Self>, Box<Self>),
+	#[poly_enum(Pure)]
+	Neg {
+		inner: Box<Self>,
+	},
+	Print(Box<Self>),
+}
+
+// `Unary` is smaller than `Unsized`, so it is only borrowed through views
+#[poly_enum::poly_enum]
+#[repr(u8)]
+enum Unsized {
+	Lit(i64),
+	Add(Box<Self>, Box<Self>),
+	#[poly_enum(Unary)]
+	Neg(Box<Self>),
+}
+
+fn lit(value: i64) -> Box<Expr> {
+	Box::new(Expr::Lit(value))
+}
+
+#[test]
+fn nested_members() {
+	let mut expr = Expr::Add(lit(1), Box::new(Expr::Neg {inner: lit(2)}));
+	let pure: &Pure = expr.cast_ref().unwrap();
+	assert_eq!(pure, &Pure::Add(Box::new(Pure::Lit(1)), Box::new(Pure::Neg {inner: Box::new(Pure::Lit(2))})));
+
+	let Some(Pure::Add(left, _)) = CastRef::<Pure>::cast_mut(&mut expr) else {
+		panic!("expected a pure expression");
+	};
+	**left = Pure::Lit(3);
+	assert_eq!(expr, Expr::Add(lit(3), Box::new(Expr::Neg {inner: lit(2)})));
+}
+
+#[test]
+fn nested_non_members() {
+	let mut expr = Expr::Add(lit(1), Box::new(Expr::Print(lit(2))));
+	assert!(CastRef::<Pure>::cast_ref(&expr).is_none());
+	assert!(CastRef::<Pure>::cast_mut(&mut expr).is_none());
+
+	let expr = Expr::Neg {inner: Box::new(Expr::Print(lit(2)))};
+	assert!(CastRef::<Pure>::cast_ref(&expr).is_none());
+	assert!(CastRef::<Num>::cast_ref(&expr).is_none());
+}
+
+#[test]
+fn between_sub_enums() {
+	let pure = Pure::Neg {inner: lit_pure(4)};
+	assert!(CastRef::<Num>::cast_ref(&pure).is_none());
+	let pure = Pure::Lit(4);
+	assert_eq!(CastRef::<Num>::cast_ref(&pure), Some(&Num::Lit(4)));
+}
+
+fn lit_pure(value: i64) -> Box<Pure> {
+	Box::new(Pure::Lit(value))
+}
+
+#[test]
+fn different_sizes() {
+	assert!(size_of::<Unary>() < size_of::<Unsized>());
+	let mut expr = Unsized::Neg(Box::new(Unsized::Lit(1)));
+	assert!(!expr.is_unary());
+	let Some(UnaryMut::Neg(inner)) = expr.as_unary_mut() else {
+		panic!("expected a negation");
+	};
+	**inner = Unsized::Add(Box::new(Unsized::Lit(2)), Box::new(Unsized::Lit(3)));
+	let Some(UnaryRef::Neg(inner)) = expr.as_unary() else {
+		panic!("expected a negation");
+	};
+	assert!(matches!(&**inner, Unsized::Add(left, right) if matches!((&**left, &**right), (Unsized::Lit(2), Unsized::Lit(3)))));
+}
@@ -0,0 +1,50 @@
+use poly_enum::{CastSlice, CastSliceMut};
+
+#[poly_enum::poly_enum(layout = shared)]
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Elements {
+	#[poly_enum(NonMetal)]
+	Carbon,
+	#[poly_enum(Oxidizer, NonMetal)]
+	Oxygen(u8),
+	#[poly_enum(Metal)]
+	Iron(u64),
+	#[poly_enum(Alkali, Metal)]
+	Sodium{charge: i8},
+}
+
+#[test]
+fn parent_to_sub() {
+	let elements = [Elements::Iron(26), Elements::Sodium{charge: 1}];
+	let metals: &[Metal] = elements.cast_slice().unwrap();
+	assert_eq!(metals.as_ptr() as *const u8, elements.as_ptr() as *const u8);
+	assert_eq!(metals, [Metal::Iron(26), Metal::Sodium{charge: 1}]);
+
+	let elements = [Elements::Iron(26), Elements::Carbon];
+	assert!(CastSlice::<Metal>::cast_slice(&elements[..]).is_none());
+	assert_eq!(CastSlice::<Metal>::cast_slice(&elements[..1]), Some(&[Metal::Iron(26)][..]));
+}
+
+#[test]
+fn widen_mut_slice() {
+	let mut metals = [Metal::Iron(1), Metal::Sodium{charge: 1}];
+	let metals_mut = &mut metals[..];
+	let elements: &[Elements] = metals_mut.cast_slice().unwrap();
+	assert_eq!(elements, [Elements::Iron(1), Elements::Sodium{charge: 1}]);
+}
+
+#[test]
+fn mutate_through_sub_enum() {
+	let mut elements = [Elements::Oxygen(8), Elements::Carbon];
+	let non_metals: &mut [NonMetal] = elements.cast_slice_mut().unwrap();
+	non_metals[1] = NonMetal::Oxygen(16);
+	let oxidizers: &mut [Oxidizer] = non_metals.cast_slice_mut().unwrap();
+	oxidizers[0] = Oxidizer::Oxygen(6);
+	assert_eq!(elements, [Elements::Oxygen(6), Elements::Oxygen(16)]);
+
+	let mut elements = [Elements::Oxygen(8), Elements::Iron(1)];
+	assert!(CastSliceMut::<NonMetal>::cast_slice_mut(&mut elements[..]).is_none());
+	let alkali: Option<&mut [Alkali]> = elements[1..].cast_slice_mut();
+	assert!(alkali.is_none());
+}
@@ -0,0 +1,79 @@
+use std::borrow::Borrow;
+
+use poly_enum::{Cast, CastRef, CastView, TryCast};
+
+#[derive(Debug, PartialEq, poly_enum::PolyEnum)]
+#[poly_derive(Debug, PartialEq)]
+#[repr(u8)]
+enum Shared {
+	#[poly_enum(Small)]
+	A(u8) = 3,
+	#[poly_enum(Small, Large)]
+	B(u16),
+	#[poly_enum(Large)]
+	C{v: u64},
+}
+
+// Without fields every sub-enum is as large as the parent, so it can be borrowed
+#[derive(Debug, PartialEq, poly_enum::PolyEnum)]
+#[poly_derive(Debug, PartialEq)]
+#[repr(u8)]
+enum Elements {
+	#[poly_enum(Metal)]
+	Iron,
+	#[poly_enum(NonMetal)]
+	Carbon,
+}
+
+#[derive(Debug, PartialEq, poly_enum::PolyEnum)]
+#[poly_derive(Debug, PartialEq)]
+enum Unshared {
+	#[poly_enum(Text)]
+	String(String),
+	#[poly_enum(Text)]
+	Str{s: &'static str},
+	#[poly_enum(Number)]
+	I64(i64),
+	#[poly_enum(Text, Number)]
+	Empty,
+}
+
+#[test]
+fn cast() {
+	assert_eq!(Cast::<Small>::cast(Shared::B(2)), Some(Small::B(2)));
+	assert_eq!(Cast::<Large>::cast(Small::A(1)), None);
+	assert_eq!(TryCast::<Small>::try_cast(Shared::C{v: 4}).unwrap_err().into_inner(), Shared::C{v: 4});
+}
+
+#[test]
+fn cast_ref() {
+	let mut value = Shared::B(2);
+	if let Some(Large::B(v)) = CastRef::<Large>::cast_mut(&mut value) {
+		*v = 7;
+	}
+	assert_eq!(value, Shared::B(7));
+	assert!(CastRef::<Small>::cast_ref(&Shared::C{v: 1}).is_none());
+
+	let parent: &Elements = Metal::Iron.borrow();
+	assert_eq!(parent, &Elements::Iron);
+	let parent: &Elements = NonMetal::Carbon.as_ref();
+	assert_eq!(parent, &Elements::Carbon);
+}
+
+#[test]
+fn cast_view() {
+	let mut value = Unshared::String("a".to_string());
+	if let Some(TextMut::String(s)) = CastView::<Text>::cast_view_mut(&mut value) {
+		s.push('b');
+	}
+	assert!(matches!(CastView::<Text>::cast_view(&value), Some(TextRef::String(s)) if s == "ab"));
+	assert!(matches!(CastView::<Text>::cast_view(&Unshared::Str{s: "c"}), Some(TextRef::Str{s: &"c"})));
+	let mut value = Unshared::Str{s: "c"};
+	if let Some(TextMut::Str{s}) = CastView::<Text>::cast_view_mut(&mut value) {
+		*s = "d";
+	}
+	assert_eq!(value, Unshared::Str{s: "d"});
+	assert!(matches!(CastView::<Number>::cast_view(&Unshared::Empty), Some(NumberRef::Empty)));
+	assert!(CastView::<Number>::cast_view(&Unshared::I64(1)).is_some());
+	assert!(CastView::<Number>::cast_view(&Unshared::Str{s: "e"}).is_none());
+}
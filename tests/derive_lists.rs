@@ -0,0 +1,67 @@
+use std::collections::HashSet;
+
+mod attribute {
+	#[poly_enum::poly_enum]
+	#[repr(u8)]
+	#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+	#[poly_derive(Int: Hash, Eq, Ord)]
+	#[poly_derive(Float: !Default)]
+	pub enum Number {
+		#[poly_enum(Int)]
+		#[default]
+		Zero,
+		#[poly_enum(Int)]
+		I64(i64),
+		#[poly_enum(Float)]
+		F64(f64),
+	}
+
+	#[poly_enum::poly_enum]
+	#[repr(u8)]
+	#[derive(Debug, Default, PartialEq)]
+	#[poly_derive(!Default, Text: Clone)]
+	pub enum Value {
+		#[default]
+		Empty,
+		#[poly_enum(Text)]
+		Str(&'static str),
+		#[poly_enum(Bytes)]
+		Bytes(&'static [u8]),
+	}
+}
+
+mod derive {
+	#[derive(Debug, Default, PartialEq, poly_enum::PolyEnum)]
+	#[poly_derive(Debug, PartialEq, std::default::Default, Float: !Default, Int: Eq, Hash)]
+	pub enum Number {
+		#[poly_enum(Int)]
+		#[default]
+		Zero,
+		#[poly_enum(Int)]
+		I64(i64),
+		#[poly_enum(Float)]
+		F64(f64),
+	}
+}
+
+#[test]
+fn attribute() {
+	use attribute::{Bytes, Float, Int, Number, Text, Value};
+	assert_eq!(Int::default(), Int::Zero);
+	assert_eq!(Number::default(), Number::Zero);
+	assert!(Int::Zero < Int::I64(1));
+	assert_eq!(HashSet::from([Int::I64(1), Int::I64(1), Int::Zero]).len(), 2);
+	assert_eq!(Float::F64(1.0).clone(), Float::F64(1.0));
+	assert_eq!(Text::Str("a").clone(), Text::Str("a"));
+	assert_eq!(Bytes::Bytes(b"a"), Bytes::Bytes(b"a"));
+	assert_eq!(Value::default(), Value::Empty);
+}
+
+#[test]
+fn derive() {
+	use derive::{Float, Int, Number};
+	assert_eq!(Int::default(), Int::Zero);
+	assert_eq!(HashSet::from([Int::I64(1), Int::Zero]).len(), 2);
+	assert_eq!(Float::F64(1.0), Float::F64(1.0));
+	assert!(matches!(Number::Zero, Number::Zero));
+}
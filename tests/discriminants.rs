@@ -0,0 +1,87 @@
+use poly_enum::CastRef;
+
+const NOBLE: u8 = 18;
+
+#[poly_enum::poly_enum]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Elements {
+	#[poly_enum(NonMetal)]
+	Carbon = 6,
+	#[poly_enum(NonMetal)]
+	Nitrogen,
+	#[poly_enum(Metal)]
+	Iron = 26,
+	#[poly_enum(Metal)]
+	Cobalt,
+	#[poly_enum(Noble)]
+	Argon = NOBLE,
+	#[poly_enum(Noble)]
+	Potassium,
+}
+
+// The derive can't rewrite the parent, so it spells out the implicit discriminants even without a `repr`
+#[derive(Clone, Copy, poly_enum::PolyEnum)]
+enum Isotopes {
+	#[poly_enum(Heavy)]
+	Iron56 = 26,
+	Carbon12 = 6,
+	#[poly_enum(Heavy)]
+	Cobalt59,
+}
+
+mod shared {
+	#[poly_enum::poly_enum(layout = shared)]
+	#[repr(u16)]
+	#[derive(Debug, PartialEq)]
+	pub enum Value {
+		#[poly_enum(Small)]
+		A(u8),
+		#[poly_enum(Small)]
+		B(u16) = 2,
+		C(u64) = 5,
+		#[poly_enum(Small)]
+		D{v: u8} = 1,
+	}
+}
+
+#[test]
+fn kept_as_written() {
+	assert_eq!(Elements::Carbon as u8, 6);
+	assert_eq!(Elements::Nitrogen as u8, 7);
+	assert_eq!(Elements::Iron as u8, 26);
+	assert_eq!(Elements::Cobalt as u8, 27);
+	assert_eq!(Elements::Argon as u8, 18);
+	assert_eq!(Elements::Potassium as u8, 19);
+}
+
+#[test]
+fn shared_with_sub_enums() {
+	assert_eq!(NonMetal::Nitrogen as u8, 7);
+	assert_eq!(Metal::Iron as u8, 26);
+	assert_eq!(Metal::Cobalt as u8, 27);
+	assert_eq!(Noble::Potassium as u8, 19);
+	assert_eq!(CastRef::<Metal>::cast_ref(&Elements::Cobalt), Some(&Metal::Cobalt));
+	assert_eq!(CastRef::<Metal>::cast_ref(&Elements::Nitrogen), None);
+}
+
+#[test]
+fn implicit_after_explicit_without_repr() {
+	assert_eq!(Isotopes::Carbon12 as isize, 6);
+	assert_eq!(Isotopes::Cobalt59 as isize, 7);
+	assert_eq!(Heavy::Iron56 as isize, 26);
+	assert_eq!(Heavy::Cobalt59 as isize, Isotopes::Cobalt59 as isize);
+}
+
+#[test]
+fn with_fields() {
+	use shared::{Small, Value};
+	let mut value = Value::D{v: 3};
+	if let Some(Small::D{v}) = CastRef::<Small>::cast_mut(&mut value) {
+		*v = 4;
+	}
+	assert_eq!(value, Value::D{v: 4});
+	assert_eq!(CastRef::<Small>::cast_ref(&Value::A(1)), Some(&Small::A(1)));
+	assert_eq!(CastRef::<Small>::cast_ref(&Value::B(5)), Some(&Small::B(5)));
+	assert_eq!(CastRef::<Small>::cast_ref(&Value::C(1)), None);
+}
@@ -46,3 +46,31 @@ fn cast_by_ref() {
 	use poly_enum::CastRef;
 	assert!(CastRef::<Metal>::cast_ref(&Elements::Florine).is_none());
 }
+
+#[test]
+fn widen_alkali() {
+	let metal: Metal = Alkali::Sodium.into();
+	assert!(matches!(metal, Metal::Sodium));
+}
+
+#[test]
+fn widen_with_question_mark() {
+	fn reject(alkali: Alkali) -> Result<(), Metal> {
+		Err(alkali)?
+	}
+	assert!(matches!(reject(Alkali::Lithium), Err(Metal::Lithium)));
+}
+
+#[test]
+fn cast_between_sub_enums_by_ref() {
+	use poly_enum::CastRef;
+	let mut metal = Metal::Sodium;
+	assert!(matches!(CastRef::<Alkali>::cast_ref(&metal), Some(Alkali::Sodium)));
+	if let Some(alkali) = CastRef::<Alkali>::cast_mut(&mut metal) {
+		*alkali = Alkali::Lithium;
+	}
+	assert!(matches!(metal, Metal::Lithium));
+	assert!(CastRef::<Alkali>::cast_ref(&Metal::Iron).is_none());
+	assert!(CastRef::<Oxidizer>::cast_ref(&NonMetal::Carbon).is_none());
+	assert!(matches!(CastRef::<Oxidizer>::cast_ref(&NonMetal::Oxygen), Some(Oxidizer::Oxygen)));
+}
@@ -0,0 +1,68 @@
+macro_rules! twice {
+	($ty:ty) => {($ty, $ty)};
+}
+
+pub struct Buffer<const N: usize>([u8; N]);
+
+#[poly_enum::poly_enum]
+#[repr(u8)]
+enum Shapes<'a, T, U, const N: usize> {
+	#[poly_enum(Pair)]
+	Pair((T, U)),
+	#[poly_enum(Fixed)]
+	Fixed([u8; N]),
+	#[poly_enum(Fixed)]
+	Buffered(Buffer<{N}>),
+	#[poly_enum(Callback)]
+	Callback(Box<dyn Fn(T) -> U + 'a>),
+	#[poly_enum(Twice)]
+	Twice(twice!(T)),
+	_Empty,
+}
+
+#[poly_enum::poly_enum]
+#[repr(u8)]
+enum Source<I: Iterator<Item = V>, V> {
+	#[poly_enum(Lazy)]
+	Iter(I),
+	#[poly_enum(Eager)]
+	Value(V),
+}
+
+#[test]
+fn tuple() {
+	let shape = Shapes::<u8, i8, 0>::from(Pair::Pair((1, -1)));
+	assert!(matches!(shape.into_pair(), Ok(Pair::Pair((1, -1)))));
+}
+
+#[test]
+fn array_length() {
+	let shape = Shapes::<u8, i8, 2>::from(Fixed::Fixed([1, 2]));
+	assert!(matches!(shape.into_fixed(), Ok(Fixed::Fixed([1, 2]))));
+	let shape = Shapes::<u8, i8, 1>::from(Fixed::Buffered(Buffer([3])));
+	assert!(matches!(shape.into_fixed(), Ok(Fixed::Buffered(Buffer([3])))));
+}
+
+#[test]
+fn trait_object() {
+	let offset = 1;
+	let shape = Shapes::<u8, i8, 0>::Callback(Box::new(|x| x as i8 - offset));
+	let Ok(Callback::Callback(callback)) = shape.into_callback() else {
+		panic!("Expected a callback");
+	};
+	assert_eq!(callback(3), 2);
+}
+
+#[test]
+fn macro_type() {
+	let shape = Shapes::<u8, i8, 0>::from(Twice::Twice((1, 2)));
+	assert!(matches!(shape.into_twice(), Ok(Twice::Twice((1, 2)))));
+}
+
+#[test]
+fn bounds_only() {
+	let source = Source::<_, u8>::from(Lazy::Iter([1, 2].into_iter()));
+	assert!(matches!(source.into_lazy(), Ok(Lazy::Iter(_))));
+	let source = Source::<std::vec::IntoIter<u8>, u8>::from(Eager::Value(1));
+	assert!(matches!(source.into_eager(), Ok(Eager::Value(1))));
+}
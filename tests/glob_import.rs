@@ -0,0 +1,21 @@
+use poly_enum::*;
+
+#[derive(Debug, PartialEq, PolyEnum)]
+#[poly_derive(Debug, PartialEq)]
+enum Elements {
+	#[poly_enum(Metal)]
+	Iron,
+	#[poly_enum(Metal)]
+	Sodium,
+	#[poly_enum(NonMetal)]
+	Carbon,
+}
+
+#[test]
+fn cast() {
+	let metal: Option<Metal> = Elements::Iron.cast();
+	assert_eq!(metal, Some(Metal::Iron));
+	let element: Option<Elements> = Metal::Sodium.cast();
+	assert_eq!(element, Some(Elements::Sodium));
+	assert_eq!(PolyEnum::<NonMetal>::cast(Elements::Iron), None);
+}
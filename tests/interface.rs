@@ -0,0 +1,60 @@
+#[poly_enum::poly_enum]
+#[repr(u32)]
+enum Elements {
+	#[poly_enum(NonMetal)]
+	Carbon,
+	#[poly_enum(Oxidizer, NonMetal)]
+	Florine,
+	#[poly_enum(Metal)]
+	Iron,
+	#[poly_enum(Alkali, Metal)]
+	Lithium,
+	#[poly_enum(Oxidizer, NonMetal)]
+	Oxygen,
+	#[poly_enum(Alkali, Metal)]
+	Sodium,
+}
+
+#[poly_enum::interface]
+fn describe(e: Elements) -> &'static str {
+	fn describe(_m: Metal) -> &'static str {"metal"}
+	fn describe(_a: Alkali) -> &'static str {"alkali"}
+	fn describe(_n: NonMetal) -> &'static str {"non-metal"}
+	fn describe(_o: Oxidizer) -> &'static str {"oxidizer"}
+}
+
+#[poly_enum::interface]
+fn react(a: &Elements, b: &Elements, scale: u32) -> u32 {
+	fn react(_a: &Alkali, _b: &Oxidizer, scale: u32) -> u32 {10 * scale}
+	fn react(_a: &Metal, _b: &Oxidizer, scale: u32) -> u32 {scale}
+	fn react(_a: &Oxidizer, _b: &Alkali, scale: u32) -> u32 {10 * scale}
+	0
+}
+
+#[poly_enum::interface]
+fn heavier(m: Metal) -> bool {
+	fn heavier(_a: Alkali) -> bool {false}
+	true
+}
+
+#[test]
+fn most_specific() {
+	assert_eq!(describe(Elements::Carbon), "non-metal");
+	assert_eq!(describe(Elements::Oxygen), "oxidizer");
+	assert_eq!(describe(Elements::Iron), "metal");
+	assert_eq!(describe(Elements::Sodium), "alkali");
+}
+
+#[test]
+fn multimethod() {
+	assert_eq!(react(&Elements::Sodium, &Elements::Oxygen, 2), 20);
+	assert_eq!(react(&Elements::Iron, &Elements::Florine, 2), 2);
+	assert_eq!(react(&Elements::Florine, &Elements::Lithium, 1), 10);
+	assert_eq!(react(&Elements::Carbon, &Elements::Oxygen, 1), 0);
+}
+
+#[test]
+fn sub_enum_interface() {
+	assert!(heavier(Metal::Iron));
+	assert!(!heavier(Metal::Lithium));
+}
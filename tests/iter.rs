@@ -0,0 +1,52 @@
+use poly_enum::PolyIterExt;
+
+#[poly_enum::poly_enum]
+#[repr(u32)]
+#[derive(Clone, Debug, PartialEq)]
+enum Elements {
+	#[poly_enum(NonMetal)]
+	Carbon,
+	#[poly_enum(Oxidizer, NonMetal)]
+	Oxygen(u8),
+	#[poly_enum(Metal)]
+	Iron(u64),
+	#[poly_enum(Alkali, Metal)]
+	Sodium,
+}
+
+fn elements() -> Vec<Elements> {
+	vec![Elements::Iron(26), Elements::Carbon, Elements::Sodium, Elements::Oxygen(8)]
+}
+
+#[test]
+fn filter_cast() {
+	let metals = elements().into_iter().filter_cast::<Metal>().collect::<Vec<_>>();
+	assert_eq!(metals, [Metal::Iron(26), Metal::Sodium]);
+	let alkali = metals.into_iter().filter_cast::<Alkali>().collect::<Vec<_>>();
+	assert_eq!(alkali, [Alkali::Sodium]);
+}
+
+#[test]
+fn filter_cast_ref() {
+	let elements = elements();
+	let non_metals = elements.iter().filter_cast_ref::<NonMetal>().collect::<Vec<_>>();
+	assert_eq!(non_metals, [&NonMetal::Carbon, &NonMetal::Oxygen(8)]);
+	assert!(std::ptr::eq(non_metals[1] as *const NonMetal as *const u8, &elements[3] as *const Elements as *const u8));
+}
+
+#[test]
+fn try_cast_all() {
+	let metals = [Elements::Iron(1), Elements::Sodium].into_iter().try_cast_all::<Metal>().unwrap();
+	assert_eq!(metals, [Metal::Iron(1), Metal::Sodium]);
+
+	let err = elements().into_iter().try_cast_all::<Metal>().unwrap_err();
+	assert_eq!(err.index(), Some(1));
+	assert_eq!(err.to_string(), "element 1: variant `Carbon` is not a member of `Metal`");
+	assert_eq!(err.into_inner(), Elements::Carbon);
+}
+
+#[test]
+fn upcast() {
+	let elements = [Alkali::Sodium].into_iter().upcast::<Metal>().upcast::<Elements>().collect::<Vec<_>>();
+	assert_eq!(elements, [Elements::Sodium]);
+}
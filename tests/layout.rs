@@ -0,0 +1,64 @@
+use std::{borrow::Borrow, mem::{align_of, size_of}};
+
+use poly_enum::CastRef;
+
+#[poly_enum::poly_enum(layout = shared)]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+enum Value {
+	#[poly_enum(Small)]
+	A(u8),
+	#[poly_enum(Small, Large)]
+	B(u16),
+	#[poly_enum(Large)]
+	C{v: u64},
+	#[poly_enum(Small)]
+	D,
+}
+
+#[poly_enum::poly_enum(layout = shared)]
+#[repr(u16)]
+#[derive(Debug, PartialEq)]
+enum Tree<'a> {
+	#[poly_enum(Leaf)]
+	Name(&'a str),
+	#[poly_enum(Leaf)]
+	Byte(u8),
+	Node(Box<Self>, Box<Self>, u64),
+}
+
+#[test]
+fn padded() {
+	assert_eq!(size_of::<Small>(), size_of::<Value>());
+	assert_eq!(align_of::<Small>(), align_of::<Value>());
+	assert_eq!(size_of::<Large>(), size_of::<Value>());
+	assert_eq!(size_of::<Leaf>(), size_of::<Tree>());
+	assert_eq!(align_of::<Leaf>(), align_of::<Tree>());
+}
+
+#[test]
+fn borrow_parent() {
+	let parent: &Value = Small::A(1).borrow();
+	assert_eq!(parent, &Value::A(1));
+	let parent: &Value = Small::D.as_ref();
+	assert_eq!(parent, &Value::D);
+	let parent: &Value = Large::C{v: 9}.as_ref();
+	assert_eq!(parent, &Value::C{v: 9});
+
+	let parent: &Tree = Leaf::Name("a").borrow();
+	assert_eq!(parent, &Tree::Name("a"));
+	let node = Tree::Node(Box::new(Tree::Byte(1)), Box::new(Tree::Name("b")), 2);
+	assert!(CastRef::<Leaf>::cast_ref(&node).is_none());
+}
+
+#[test]
+fn matches_by_value() {
+	let small = Small::B(4);
+	let v = match small {
+		Small::A(v) => v as u16,
+		Small::B(v) => v,
+		Small::D => 0,
+	};
+	assert_eq!(v, 4);
+	assert_eq!(CastRef::<Small>::cast_ref(&Value::B(4)), Some(&small));
+}
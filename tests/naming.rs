@@ -0,0 +1,76 @@
+use std::borrow::Borrow;
+
+use poly_enum::{Cast, CastRef};
+
+mod nested {
+	#[poly_enum::poly_enum(module = value, layout = shared)]
+	#[repr(u8)]
+	#[derive(Clone, Debug, PartialEq)]
+	pub(crate) enum Value {
+		#[poly_enum(Int, Small)]
+		U8(u8),
+		#[poly_enum(Int)]
+		I64(i64),
+		#[poly_enum(Float)]
+		F64(f64),
+	}
+
+	// The same sub-enum names don't collide in another module
+	#[poly_enum::poly_enum(module = token, partition = clone)]
+	#[repr(u8)]
+	#[derive(Clone, Debug, PartialEq)]
+	enum Token {
+		#[poly_enum(Int)]
+		Int(i64),
+		#[poly_enum(Float, Small)]
+		Half(u16),
+		#[poly_enum(Small)]
+		Byte(u8),
+	}
+
+	#[test]
+	fn private_parent() {
+		let partition = Token::partition([Token::Int(1), Token::Half(2)]);
+		assert_eq!(partition.int, [token::Int::Int(1)]);
+		assert_eq!(partition.float, [token::Float::Half(2)]);
+		assert_eq!(partition.small, [token::Small::Half(2)]);
+		assert_eq!(token::Float::Half(2).into_small(), Ok(token::Small::Half(2)));
+		assert_eq!(Token::from(token::Small::Byte(3)), Token::Byte(3));
+	}
+}
+
+mod affixed {
+	#[poly_enum::poly_enum(prefix = Value, suffix = Kind)]
+	#[repr(u8)]
+	#[derive(Clone, Copy, Debug, PartialEq)]
+	pub enum Value {
+		#[poly_enum(Int)]
+		I64(i64),
+		#[poly_enum(Float)]
+		F64(f64),
+		#[poly_enum(Int, Float)]
+		Zero,
+	}
+}
+
+#[test]
+fn module() {
+	use nested::{value, Value};
+	assert_eq!(Cast::<value::Int>::cast(Value::U8(1)), Some(value::Int::U8(1)));
+	assert_eq!(Value::from(value::Small::U8(2)), Value::U8(2));
+	assert_eq!(value::Int::from(value::Small::U8(3)), value::Int::U8(3));
+	assert_eq!(CastRef::<value::Float>::cast_ref(&Value::F64(1.0)), Some(&value::Float::F64(1.0)));
+	assert_eq!(Value::I64(4).as_int(), Some(&value::Int::I64(4)));
+	assert_eq!(value::Int::I64(5).into_small(), Err(value::Int::I64(5)));
+	let borrowed: &Value = value::Small::U8(6).borrow();
+	assert_eq!(borrowed, &Value::U8(6));
+}
+
+#[test]
+fn prefix_and_suffix() {
+	use affixed::{Value, ValueFloatKind, ValueIntKind};
+	assert_eq!(Value::I64(1).into_int(), Ok(ValueIntKind::I64(1)));
+	assert_eq!(Value::F64(1.0).as_float(), Some(&ValueFloatKind::F64(1.0)));
+	assert_eq!(ValueIntKind::Zero.into_float(), Ok(ValueFloatKind::Zero));
+	assert_eq!(Cast::<ValueIntKind>::cast(Value::F64(1.0)), None);
+}
@@ -0,0 +1,50 @@
+use poly_enum::Cast;
+
+#[poly_enum::poly_enum]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+enum Grade {
+	#[poly_enum(Pass, Any)]
+	A,
+	#[poly_enum(Pass, Any)]
+	B,
+	#[poly_enum(Pass, Any)]
+	C,
+	#[poly_enum(Pass, Any)]
+	D,
+	#[poly_enum(Fail, Any)]
+	E,
+	#[poly_enum(Fail, Any)]
+	F,
+	Incomplete,
+}
+
+#[poly_enum::poly_enum]
+#[repr(u8)]
+#[derive(Debug, PartialEq)]
+enum Value<'a, T, U> {
+	#[poly_enum(Owned, Pair)]
+	Both(U, T),
+	#[poly_enum(Borrowed)]
+	Str(&'a str),
+	#[poly_enum(Owned, Borrowed)]
+	Empty,
+}
+
+#[test]
+fn variants_keep_the_parents_order() {
+	assert!(Pass::A < Pass::B && Pass::B < Pass::C && Pass::C < Pass::D);
+	assert!(Any::A < Any::D && Any::D < Any::E && Any::E < Any::F);
+	assert_eq!(format!("{:?}", Any::ALL), "[A, B, C, D, E, F]");
+	assert_eq!(Fail::ALL, [Fail::E, Fail::F]);
+	assert!(Grade::F < Grade::Incomplete);
+}
+
+#[test]
+fn generics_keep_the_parents_order() {
+	let pair: Pair<u8, &str> = Pair::Both("a", 1);
+	assert_eq!(Value::from(pair), Value::Both("a", 1));
+	let owned: Owned<u8, &str> = Owned::Empty;
+	assert_eq!(Cast::<Borrowed<'static>>::cast(owned), Some(Borrowed::Empty));
+	assert_eq!(Cast::<Owned<u8, &str>>::cast(Borrowed::Str("b")), None);
+}
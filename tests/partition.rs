@@ -0,0 +1,102 @@
+#[poly_enum::poly_enum]
+#[repr(u32)]
+#[derive(Clone, Debug, PartialEq)]
+enum Elements {
+	#[poly_enum(NonMetal)]
+	Carbon,
+	#[poly_enum(Oxidizer, NonMetal)]
+	Oxygen(u8),
+	#[poly_enum(Metal)]
+	Iron(u64),
+	#[poly_enum(Alkali, Metal)]
+	Sodium,
+	Argon,
+}
+
+mod prioritised {
+	#[poly_enum::poly_enum(partition(Metal))]
+	#[repr(u32)]
+	#[derive(Clone, Debug, PartialEq)]
+	pub enum Prioritised {
+		#[poly_enum(Alkali)]
+		Lithium,
+		#[poly_enum(Alkali, Metal)]
+		Sodium,
+		#[poly_enum(Metal)]
+		Iron,
+	}
+}
+
+mod cloned {
+	#[poly_enum::poly_enum(partition = clone)]
+	#[repr(u32)]
+	#[derive(Clone, Debug, PartialEq)]
+	pub enum Cloned {
+		#[poly_enum(NonMetal)]
+		Carbon,
+		#[poly_enum(Oxidizer, NonMetal)]
+		Oxygen(String),
+		Argon,
+	}
+}
+
+mod keywords {
+	#[poly_enum::poly_enum]
+	#[repr(u8)]
+	#[derive(Debug, PartialEq)]
+	pub enum Keywords {
+		#[poly_enum(Gen)]
+		Yield,
+		#[poly_enum(Type)]
+		Struct,
+		#[poly_enum(Crate)]
+		Mod,
+		Fn,
+	}
+}
+
+use cloned::Cloned;
+use prioritised::Prioritised;
+
+#[test]
+fn first_mentioned() {
+	let partition = Elements::partition([
+		Elements::Sodium,
+		Elements::Oxygen(8),
+		Elements::Argon,
+		Elements::Iron(26),
+		Elements::Carbon,
+	]);
+	assert!(partition.oxidizer.is_empty());
+	assert_eq!(partition.non_metal, [NonMetal::Oxygen(8), NonMetal::Carbon]);
+	assert_eq!(partition.metal, [Metal::Sodium, Metal::Iron(26)]);
+	assert!(partition.alkali.is_empty());
+	assert_eq!(partition.remainder, [Elements::Argon]);
+}
+
+#[test]
+fn declared_priority() {
+	let partition = Prioritised::partition(vec![Prioritised::Lithium, Prioritised::Sodium, Prioritised::Iron]);
+	assert_eq!(partition.alkali, [prioritised::Alkali::Lithium]);
+	assert_eq!(partition.metal, [prioritised::Metal::Sodium, prioritised::Metal::Iron]);
+	assert!(partition.remainder.is_empty());
+}
+
+#[test]
+fn cloned_into_every_bucket() {
+	let partition = Cloned::partition([Cloned::Oxygen("O".to_string()), Cloned::Carbon, Cloned::Argon]);
+	assert_eq!(partition.non_metal, [cloned::NonMetal::Oxygen("O".to_string()), cloned::NonMetal::Carbon]);
+	assert_eq!(partition.oxidizer, [cloned::Oxidizer::Oxygen("O".to_string())]);
+	assert_eq!(partition.remainder, [Cloned::Argon]);
+}
+
+#[test]
+fn keyword_fields() {
+	use keywords::{Crate, Gen, Keywords, Type};
+	let partition = Keywords::partition([Keywords::Yield, Keywords::Struct, Keywords::Mod, Keywords::Fn]);
+	assert_eq!(partition.r#gen, [Gen::Yield]);
+	assert_eq!(partition.r#type, [Type::Struct]);
+	assert_eq!(partition.crate_, [Crate::Mod]);
+	assert!(Keywords::Yield.is_gen());
+	assert!(Keywords::Mod.as_crate_mut().is_some());
+}
@@ -0,0 +1,130 @@
+use std::mem::{align_of, size_of};
+
+use poly_enum::CastRef;
+
+mod c_u8 {
+	#[poly_enum::poly_enum(layout = shared)]
+	#[repr(C, u8)]
+	#[derive(Debug, PartialEq)]
+	pub enum Value {
+		#[poly_enum(Small)]
+		A(u8),
+		#[poly_enum(Small, Large)]
+		B(u16),
+		#[poly_enum(Large)]
+		C{v: u64},
+	}
+}
+
+// The fields of `Small` follow a less aligned union, so it is only borrowed through views
+mod c_mixed {
+	#[poly_enum::poly_enum]
+	#[repr(C, u8)]
+	#[derive(Debug, PartialEq)]
+	pub enum Value {
+		#[poly_enum(Small)]
+		A(u8),
+		B(u64),
+	}
+}
+
+mod aligned {
+	#[poly_enum::poly_enum(layout = shared)]
+	#[repr(u8, align(16))]
+	#[derive(Debug, PartialEq)]
+	pub enum Value {
+		#[poly_enum(Small)]
+		A(u8),
+		B(u32),
+	}
+}
+
+mod stacked {
+	#[poly_enum::poly_enum]
+	#[repr(u16)]
+	#[repr(align(8))]
+	#[derive(Debug, PartialEq)]
+	pub enum Value {
+		#[poly_enum(Small)]
+		A(u8),
+		B(u32),
+	}
+}
+
+mod c {
+	#[poly_enum::poly_enum]
+	#[repr(C)]
+	#[derive(Clone, Copy, Debug, PartialEq)]
+	pub enum Elements {
+		#[poly_enum(Metal)]
+		Iron = 26,
+		#[poly_enum(NonMetal)]
+		Carbon = 6,
+		#[poly_enum(Metal)]
+		Cobalt,
+	}
+}
+
+mod auto {
+	#[poly_enum::poly_enum(repr = auto)]
+	#[derive(Debug, PartialEq)]
+	pub enum Small {
+		#[poly_enum(Positive)]
+		A(u8),
+		B(u16),
+	}
+
+	#[poly_enum::poly_enum(repr = auto)]
+	#[derive(Debug, PartialEq)]
+	pub enum Wide {
+		#[poly_enum(NonNegative)]
+		A(u8) = 300,
+		#[poly_enum(Negative)]
+		B = -1,
+	}
+}
+
+#[test]
+fn c_with_primitive() {
+	use c_u8::{Large, Small, Value};
+	assert_eq!(CastRef::<Small>::cast_ref(&Value::A(1)), Some(&Small::A(1)));
+	assert_eq!(CastRef::<Large>::cast_ref(&Value::C{v: 2}), Some(&Large::C{v: 2}));
+	assert_eq!(CastRef::<Large>::cast_ref(&Value::A(1)), None);
+}
+
+#[test]
+fn c_with_mixed_alignments() {
+	use c_mixed::{Small, SmallRef, Value};
+	assert!(size_of::<Small>() < size_of::<Value>());
+	assert_eq!(Value::A(1).into_small(), Ok(Small::A(1)));
+	assert_eq!(Value::B(2).into_small(), Err(Value::B(2)));
+	assert!(matches!(Value::A(3).as_small(), Some(SmallRef::A(3))));
+}
+
+#[test]
+fn modifiers_are_copied() {
+	assert_eq!(align_of::<aligned::Small>(), 16);
+	assert_eq!(size_of::<aligned::Small>(), size_of::<aligned::Value>());
+	assert_eq!(CastRef::<aligned::Small>::cast_ref(&aligned::Value::A(3)), Some(&aligned::Small::A(3)));
+	assert_eq!(CastRef::<aligned::Small>::cast_ref(&aligned::Value::B(3)), None);
+	assert_eq!(align_of::<stacked::Small>(), 8);
+	assert_eq!(CastRef::<stacked::Small>::cast_ref(&stacked::Value::B(3)), None);
+}
+
+#[test]
+fn c_alone() {
+	use c::{Elements, Metal};
+	assert_eq!(Metal::Cobalt as isize, 7);
+	assert_eq!(size_of::<Metal>(), size_of::<Elements>());
+	assert_eq!(CastRef::<Metal>::cast_ref(&Elements::Cobalt), Some(&Metal::Cobalt));
+	assert_eq!(CastRef::<Metal>::cast_ref(&Elements::Carbon), None);
+}
+
+#[test]
+fn inserted() {
+	assert_eq!(size_of::<auto::Small>(), 2 * size_of::<u16>());
+	assert_eq!(CastRef::<auto::Positive>::cast_ref(&auto::Small::B(2)), None);
+	assert_eq!(size_of::<auto::Wide>(), 4);
+	assert_eq!(auto::Negative::B as i16, -1);
+	assert_eq!(CastRef::<auto::NonNegative>::cast_ref(&auto::Wide::A(1)), Some(&auto::NonNegative::A(1)));
+}
@@ -0,0 +1,74 @@
+#![deny(missing_docs)]
+//! Sub-enums pass `missing_docs` and take their own visibility and attributes.
+
+use std::collections::HashSet;
+
+/// A public hierarchy with a crate-private sub-enum
+pub mod attribute {
+	/// Numbers
+	#[poly_enum::poly_enum]
+	#[repr(u8)]
+	#[derive(Clone, Copy, Debug, PartialEq)]
+	#[allow(clippy::enum_variant_names)]
+	#[must_use]
+	#[poly_vis(Float, pub(crate))]
+	#[poly_attr(Int, doc = "Whole numbers", derive(Eq, Hash))]
+	pub enum Number {
+		/// Nothing
+		#[poly_enum(Int, Float)]
+		NoValue,
+		/// An integer
+		#[poly_enum(Int)]
+		IntValue(i64),
+		/// A float
+		#[poly_enum(Float)]
+		FloatValue{
+			/// The value
+			value: f64,
+		},
+	}
+}
+
+/// A public hierarchy without a `repr`, borrowed through views
+pub mod derive {
+	/// Numbers
+	#[derive(Debug, PartialEq, poly_enum::PolyEnum)]
+	#[poly_derive(Debug, PartialEq)]
+	#[poly_vis(Float, pub(crate))]
+	#[poly_attr(Int, derive(Clone, Copy))]
+	pub enum Number {
+		/// An integer
+		#[poly_enum(Int)]
+		I64(i64),
+		/// A float
+		#[poly_enum(Float)]
+		F64{
+			/// The value
+			value: f64,
+		},
+	}
+}
+
+#[test]
+fn attribute() {
+	use attribute::{Float, Int, Number};
+	assert_eq!(HashSet::from([Int::IntValue(1), Int::IntValue(1), Int::NoValue]).len(), 2);
+	assert_eq!(Number::FloatValue{value: 1.0}.as_float(), Some(&Float::FloatValue{value: 1.0}));
+	assert_eq!(Number::IntValue(1).into_int(), Ok(Int::IntValue(1)));
+	assert!(Int::NoValue.is_float());
+	assert_eq!(Int::NoValue.into_float(), Ok(Float::NoValue));
+}
+
+#[test]
+fn derive() {
+	use derive::{Float, FloatMut, FloatRef, Int, Number};
+	let value = Int::I64(1);
+	assert_eq!(Number::from(value), Number::I64(1));
+	assert_eq!(value, Int::I64(1));
+	assert!(matches!(Number::F64{value: 2.0}.as_float(), Some(FloatRef::F64{value: 2.0})));
+	let mut number = Number::from(Float::F64{value: 2.0});
+	if let Some(FloatMut::F64{value}) = number.as_float_mut() {
+		*value += 1.0;
+	}
+	assert!(matches!(number, Number::F64{value} if value == 3.0));
+}
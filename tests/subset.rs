@@ -0,0 +1,25 @@
+#[poly_enum::poly_enum]
+#[repr(u8)]
+#[derive(Debug, PartialEq)]
+// `Round` and `Curved` have the same variants on purpose, which is warned about
+#[allow(deprecated)]
+enum Shape {
+	#[poly_enum(Round, Curved, Closed)]
+	Circle(f32),
+	#[poly_enum(Round, Curved, Closed)]
+	Ellipse(f32, f32),
+	#[poly_enum(Closed)]
+	Square(f32),
+	_Line,
+}
+
+#[test]
+fn subset() {
+	assert_eq!(Closed::from(Round::Circle(1.0)), Closed::Circle(1.0));
+}
+
+#[test]
+fn identical() {
+	assert_eq!(Curved::from(Round::Ellipse(1.0, 2.0)), Curved::Ellipse(1.0, 2.0));
+	assert_eq!(Round::from(Curved::Circle(1.0)), Round::Circle(1.0));
+}
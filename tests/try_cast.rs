@@ -0,0 +1,95 @@
+use std::{error::Error, rc::Rc};
+
+use poly_enum::TryCast;
+
+#[poly_enum::poly_enum]
+#[repr(u32)]
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+	#[poly_enum(Text)]
+	String(String),
+	#[poly_enum(Number)]
+	I64(i64),
+	#[poly_enum(Text)]
+	List(Vec<Self>),
+}
+
+#[test]
+fn returns_original() {
+	let err = TryCast::<Number>::try_cast(Value::String("ten".to_string())).unwrap_err();
+	assert_eq!(err.variant(), "String");
+	assert_eq!(err.target(), "Number");
+	assert_eq!(err.index(), None);
+	assert_eq!(err.into_inner(), Value::String("ten".to_string()));
+}
+
+#[test]
+fn between_sub_enums() {
+	let err = TryCast::<Number>::try_cast(Text::String("ten".to_string())).unwrap_err();
+	assert_eq!(err.to_string(), "variant `String` is not a member of `Number`");
+	assert_eq!(err.into_inner(), Text::String("ten".to_string()));
+}
+
+#[test]
+fn is_error() {
+	let err: Box<dyn Error> = Box::new(TryCast::<Text>::try_cast(Value::I64(10)).unwrap_err());
+	assert_eq!(err.to_string(), "variant `I64` is not a member of `Text`");
+}
+
+#[test]
+fn nested_self() {
+	let list = Value::List(vec![Value::String("a".to_string()), Value::I64(1)]);
+	let err = TryCast::<Text>::try_cast(list).unwrap_err();
+	assert_eq!(err.variant(), "I64");
+	assert_eq!(err.index(), Some(1));
+	assert_eq!(err.into_inner(), Value::List(vec![Value::String("a".to_string()), Value::I64(1)]));
+
+	let list = Value::List(vec![Value::String("a".to_string())]);
+	assert_eq!(TryCast::<Text>::try_cast(list).unwrap(), Text::List(vec![Text::String("a".to_string())]));
+}
+
+#[test]
+fn vec_index() {
+	let values = vec![Value::I64(1), Value::I64(2), Value::String("three".to_string()), Value::I64(4)];
+	let err = TryCast::<Vec<Number>>::try_cast(values).unwrap_err();
+	assert_eq!(err.index(), Some(2));
+	assert_eq!(err.to_string(), "element 2: variant `String` is not a member of `Number`");
+	assert_eq!(err.into_inner(), vec![Value::I64(1), Value::I64(2), Value::String("three".to_string()), Value::I64(4)]);
+
+	let mut values = Vec::with_capacity(16);
+	values.push(Value::String("one".to_string()));
+	let buffer = values.as_ptr();
+	let values = TryCast::<Vec<Number>>::try_cast(values).unwrap_err().into_inner();
+	assert_eq!((values.as_ptr(), values.capacity()), (buffer, 16));
+}
+
+#[test]
+fn shared_rc() {
+	let value = Rc::new(Value::I64(5));
+	let shared = value.clone();
+	let err = TryCast::<Rc<Text>>::try_cast(value).unwrap_err();
+	assert!(Rc::ptr_eq(&err.into_inner(), &shared));
+
+	let err = TryCast::<Rc<Text>>::try_cast(Rc::new(Value::I64(5))).unwrap_err();
+	assert_eq!(*err.into_inner(), Value::I64(5));
+}
+
+#[test]
+fn unique_pointers_untouched() {
+	let value = Rc::new(Value::I64(5));
+	let weak = Rc::downgrade(&value);
+	let value = TryCast::<Rc<Text>>::try_cast(value).unwrap_err().into_inner();
+	assert!(Rc::ptr_eq(&weak.upgrade().unwrap(), &value));
+
+	let value = Box::new(Value::I64(5));
+	let address = &*value as *const Value;
+	let value = TryCast::<Box<Text>>::try_cast(value).unwrap_err().into_inner();
+	assert_eq!(&*value as *const Value, address);
+
+	let list = vec![Value::String("a".to_string()), Value::I64(1)];
+	let buffer = list.as_ptr();
+	let Value::List(list) = TryCast::<Text>::try_cast(Value::List(list)).unwrap_err().into_inner() else {
+		panic!("expected a list");
+	};
+	assert_eq!(list.as_ptr(), buffer);
+}
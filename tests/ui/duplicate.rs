@@ -0,0 +1,11 @@
+#[poly_enum::poly_enum]
+#[repr(u8)]
+enum Elements {
+	#[poly_enum(Metal, Metal)]
+	Iron,
+	Carbon,
+}
+
+fn main() {
+	let _ = Metal::Iron;
+}
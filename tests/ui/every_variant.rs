@@ -0,0 +1,12 @@
+#[poly_enum::poly_enum]
+#[repr(u8)]
+enum Elements {
+	#[poly_enum(Element)]
+	Iron,
+	#[poly_enum(Element)]
+	Carbon,
+}
+
+fn main() {
+	let _ = Element::Iron;
+}
@@ -0,0 +1,13 @@
+#[poly_enum::poly_enum]
+#[repr(u8)]
+enum Elements {
+	#[poly_enum(metals::Metal)]
+	Iron,
+	#[poly_enum(Metal)]
+	Cobalt,
+	Carbon,
+}
+
+fn main() {
+	let _ = Metal::Cobalt;
+}
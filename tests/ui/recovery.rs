@@ -0,0 +1,24 @@
+use poly_enum::{Cast, TryCast};
+
+#[poly_enum::poly_enum]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Elements {
+	#[poly_enum(Metal)]
+	Iron,
+	#[poly_enum(Metal, Alkali)]
+	Sodium,
+	#[poly_enum(NonMetal, Metal::Heavy)]
+	Carbon,
+}
+
+fn main() {
+	let metal: Metal = Elements::Iron.cast().unwrap();
+	assert_eq!(Metal::from(Alkali::Sodium), Metal::Sodium);
+	assert!(Elements::Sodium.is_alkali());
+	let carbon: NonMetal = Elements::Carbon.try_cast().unwrap();
+	let partition = Elements::partition([Elements::Iron, Elements::Carbon]);
+	assert_eq!(partition.metal, [metal]);
+	assert_eq!(partition.non_metal, [carbon]);
+	assert_eq!(Metal::ALL, [Metal::Iron, Metal::Sodium]);
+}
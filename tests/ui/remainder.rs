@@ -0,0 +1,13 @@
+#[poly_enum::poly_enum]
+#[repr(u8)]
+enum Elements {
+	#[poly_enum(Remainder)]
+	Argon,
+	#[poly_enum(Metal)]
+	Iron,
+	Carbon,
+}
+
+fn main() {
+	let _ = (Metal::Iron, Elements::partition([Elements::Iron, Elements::Argon]).remainder);
+}
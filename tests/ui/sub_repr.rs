@@ -0,0 +1,15 @@
+use poly_enum::CastRef;
+
+#[poly_enum::poly_enum]
+#[repr(u8)]
+#[poly_attr(Small, repr(align(64)))]
+enum Value {
+	#[poly_enum(Small)]
+	A(u8),
+	B(u32),
+}
+
+fn main() {
+	let values = [Value::A(1), Value::B(2)];
+	let _ = CastRef::<Small>::cast_ref(&values[1]);
+}
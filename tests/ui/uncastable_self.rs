@@ -0,0 +1,13 @@
+#[poly_enum::poly_enum]
+#[repr(u8)]
+enum Expr {
+	#[poly_enum(Unary)]
+	Neg(Box<Self>),
+	#[poly_enum(Unary)]
+	Maybe(Option<Box<Self>>),
+	Zero,
+}
+
+fn main() {
+	let _ = (Expr::Neg(Box::new(Expr::Zero)).into_unary(), Expr::Maybe(None));
+}
@@ -0,0 +1,18 @@
+#[poly_enum::poly_enum]
+#[repr(u8)]
+#[poly_derive(Debug)]
+#[poly_derive(Zinc: Clone)]
+#[poly_derive(Metal: PartialEq)]
+#[poly_derive(Argon: Clone)]
+#[poly_derive(Boron: Clone)]
+enum Elements {
+	#[poly_enum(Metal)]
+	#[poly_attr(Tin, doc = "Tin")]
+	#[poly_attr(Copper, doc = "Copper")]
+	#[poly_attr(Gold, doc = "Gold")]
+	Iron,
+	#[poly_attr(Mercury, doc = "Mercury")]
+	Carbon(#[poly_attr(Lead, doc = "Lead")] u8),
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+#[poly_enum::poly_enum(layout = shared)]
+#[repr(u8)]
+enum Value<T> {
+	#[poly_enum(Small)]
+	U8(u8),
+	Other(T),
+}
+
+fn main() {
+	let _ = (Small::U8(1), Value::<u16>::Other(2));
+}
@@ -0,0 +1,61 @@
+mod attribute {
+	#[poly_enum::poly_enum]
+	#[repr(u8)]
+	#[derive(Debug, Default, PartialEq)]
+	pub enum Number {
+		#[poly_enum(Int, Float)]
+		#[poly_attr(Self, default)]
+		#[poly_attr(Float, default)]
+		Zero,
+		#[poly_enum(Int)]
+		#[poly_attr(Int, default)]
+		One,
+		#[poly_enum(Int)]
+		Big(#[poly_attr(Int, doc = "The value")] u64),
+		#[poly_enum(Float)]
+		Half,
+	}
+}
+
+mod derive {
+	#[derive(Debug, Default, PartialEq, poly_enum::PolyEnum)]
+	#[poly_derive(Debug, Default, PartialEq)]
+	pub enum Number {
+		#[poly_enum(Int, Float)]
+		#[default]
+		#[poly_attr(Self, default)]
+		#[poly_attr(Float, default)]
+		Zero,
+		#[poly_enum(Int)]
+		#[poly_attr(Int, default)]
+		One,
+		#[poly_enum(Int)]
+		Big{#[poly_attr(Int, doc = "The value")] value: u64},
+		#[poly_enum(Float)]
+		Half,
+	}
+}
+
+#[test]
+fn attribute() {
+	use attribute::{Float, Int, Number};
+	assert_eq!(Number::default(), Number::Zero);
+	assert_eq!(Int::default(), Int::One);
+	assert_eq!(Float::default(), Float::Zero);
+	assert_eq!(Number::from(Int::Big(2)), Number::Big(2));
+	assert_eq!(Number::from(Float::Half), Number::Half);
+}
+
+#[test]
+fn derive() {
+	use derive::{Float, Int, IntMut, IntRef, Number};
+	assert_eq!(Number::default(), Number::Zero);
+	assert_eq!(Int::default(), Int::One);
+	assert_eq!(Float::default(), Float::Zero);
+	let mut number = Number::from(Int::Big{value: 2});
+	if let Some(IntMut::Big{value}) = number.as_int_mut() {
+		*value += 1;
+	}
+	assert!(matches!(number.as_int(), Some(IntRef::Big{value: 3})));
+	assert_eq!(Number::from(Float::Half), Number::Half);
+}
@@ -0,0 +1,61 @@
+#[poly_enum::poly_enum]
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Elements {
+	#[poly_enum(NonMetal)]
+	Carbon,
+	#[poly_enum(Metal)]
+	Iron,
+	#[poly_enum(Alkali, Metal)]
+	Lithium,
+	#[poly_enum(NonMetal)]
+	Oxygen,
+	#[poly_enum(Alkali, Metal)]
+	Sodium,
+}
+
+mod mixed {
+	#[poly_enum::poly_enum(layout = shared)]
+	#[repr(u8)]
+	#[derive(Clone, Copy, Debug, PartialEq)]
+	pub enum Value {
+		#[poly_enum(Flag)]
+		On,
+		#[poly_enum(Flag)]
+		Off,
+		Number(u64),
+	}
+}
+
+#[test]
+fn parent() {
+	assert_eq!(Elements::COUNT, 5);
+	assert_eq!(Elements::ALL, [Elements::Carbon, Elements::Iron, Elements::Lithium, Elements::Oxygen, Elements::Sodium]);
+	for (i, element) in Elements::ALL.iter().enumerate() {
+		assert_eq!(element.variant_index(), i);
+		assert_eq!(Elements::from_variant_index(i), Some(*element));
+	}
+	assert_eq!(Elements::from_variant_index(5), None);
+}
+
+#[test]
+fn sub_enums() {
+	assert_eq!(Metal::ALL, [Metal::Iron, Metal::Lithium, Metal::Sodium]);
+	assert_eq!(Metal::COUNT, 3);
+	assert_eq!(Metal::Sodium.variant_index(), 2);
+	assert_eq!(Metal::from_variant_index(1), Some(Metal::Lithium));
+	assert_eq!(NonMetal::ALL, [NonMetal::Carbon, NonMetal::Oxygen]);
+	assert_eq!(Alkali::from_variant_index(2), None);
+
+	const LOOKUP: [&str; Alkali::COUNT] = ["Li", "Na"];
+	assert_eq!(LOOKUP[Alkali::Sodium.variant_index()], "Na");
+}
+
+#[test]
+fn fieldless_sub_enum_of_padded_parent() {
+	use mixed::Flag;
+	assert_eq!(Flag::ALL, [Flag::On, Flag::Off]);
+	assert_eq!(Flag::Off.variant_index(), 1);
+	assert_eq!(mixed::Value::from(Flag::On), mixed::Value::On);
+	assert!(!mixed::Value::Number(1).is_flag());
+}
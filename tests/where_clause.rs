@@ -12,3 +12,24 @@ enum AnyPtr<T> where T: Clone {
 	#[poly_enum(NotAPointer)]
 	None,
 }
+
+#[poly_enum::poly_enum]
+#[repr(u8)]
+#[derive(Debug, PartialEq)]
+enum Pair<'a, 'b, T: From<U>, U = char> where 'a: 'b, T: Clone + 'a, U: Copy, String: Clone {
+	#[poly_enum(Left, Borrowed)]
+	Left(&'a T),
+	#[poly_enum(Right)]
+	Right(U),
+	#[poly_enum(Borrowed)]
+	Short(&'b str),
+}
+
+#[test]
+fn pruned() {
+	let value = String::from("left");
+	let left = Left::<String>::Left(&value);
+	assert_eq!(Borrowed::<String>::from(left), Borrowed::Left(&value));
+	assert_eq!(Pair::<String>::Right('r').into_right(), Ok(Right::Right('r')));
+	assert_eq!(Pair::<String>::Short("short").into_borrowed(), Ok(Borrowed::Short("short")));
+}